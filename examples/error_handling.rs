@@ -65,6 +65,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             raw_response,
             serde_error,
             status,
+            ..
         }) => {
             println!("Deserialization Failed!");
             println!("  Status: {}", status);
@@ -87,14 +88,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             raw_response: "Server error".to_string().into_boxed_str(),
             headers: Box::new(http::HeaderMap::new()),
             rate_limit_info: None,
+            request: None,
         },
         Error::HttpError {
             status: http::StatusCode::BAD_REQUEST,
             raw_response: "Bad request".to_string().into_boxed_str(),
             headers: Box::new(http::HeaderMap::new()),
             rate_limit_info: None,
+            request: None,
         },
-        Error::Timeout,
+        Error::Timeout { request: None },
         Error::ConfigurationError("Invalid config".to_string()),
     ];
 
@@ -117,7 +120,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Err(Error::Network(e)) => {
             println!("Network Error!");
             println!("  Error: {}", e);
-            println!("  Is timeout: {}", e.is_timeout());
+            println!("  Kind: {:?}", e.kind());
             println!("  Is connect error: {}", e.is_connect());
         }
         Err(e) => println!("Other error: {}", e),
@@ -0,0 +1,400 @@
+//! Client-side load balancing across multiple base URLs.
+//!
+//! A client built with [`ClientBuilder::base_urls`](crate::ClientBuilder::base_urls)
+//! picks an endpoint for every attempt, including hedged requests, using its
+//! [`BalanceStrategy`]. Endpoints that repeatedly fail to connect, time out or
+//! answer with a 5xx status are ejected for a while, and retries prefer
+//! endpoints the request hasn't already failed on.
+
+use rand::Rng;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use url::Url;
+
+/// How long an endpoint is ejected for when the ejection duration is too long
+/// to represent.
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+/// How an endpoint is chosen for each attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BalanceStrategy {
+    /// Cycle through the endpoints in order.
+    #[default]
+    RoundRobin,
+
+    /// Pick an endpoint at random.
+    Random,
+
+    /// Pick the endpoint with the fewest requests in flight from this client.
+    LeastOutstanding,
+
+    /// Pick two endpoints at random and use the one with fewer requests in flight.
+    ///
+    /// This spreads load nearly as well as [`LeastOutstanding`](BalanceStrategy::LeastOutstanding)
+    /// without sending every request to the same endpoint after a burst.
+    PowerOfTwoChoices,
+}
+
+/// Configuration for load balancing across multiple base URLs.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::balance::{BalanceConfig, BalanceStrategy};
+/// use std::time::Duration;
+///
+/// let config = BalanceConfig::builder()
+///     .strategy(BalanceStrategy::PowerOfTwoChoices)
+///     .failure_threshold(5)
+///     .ejection_duration(Duration::from_secs(10))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BalanceConfig {
+    /// How an endpoint is chosen for each attempt. Defaults to round robin.
+    pub strategy: BalanceStrategy,
+
+    /// The number of consecutive failures after which an endpoint is ejected.
+    ///
+    /// Connection errors, timeouts and 5xx responses count as failures, and any
+    /// other response resets the count. Defaults to `3`; `0` disables ejection.
+    pub failure_threshold: u32,
+
+    /// How long an ejected endpoint is skipped. Defaults to 30 seconds.
+    ///
+    /// Once it's back, a single failure ejects it again until it succeeds.
+    pub ejection_duration: Duration,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        Self {
+            strategy: BalanceStrategy::RoundRobin,
+            failure_threshold: 3,
+            ejection_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+impl BalanceConfig {
+    /// Creates a new builder for configuring load balancing.
+    pub fn builder() -> BalanceConfigBuilder {
+        BalanceConfigBuilder {
+            config: Self::default(),
+        }
+    }
+}
+
+/// Builder for `BalanceConfig`.
+pub struct BalanceConfigBuilder {
+    config: BalanceConfig,
+}
+
+impl BalanceConfigBuilder {
+    /// Sets how an endpoint is chosen for each attempt.
+    pub fn strategy(mut self, strategy: BalanceStrategy) -> Self {
+        self.config.strategy = strategy;
+        self
+    }
+
+    /// Sets the number of consecutive failures after which an endpoint is ejected.
+    pub fn failure_threshold(mut self, threshold: u32) -> Self {
+        self.config.failure_threshold = threshold;
+        self
+    }
+
+    /// Sets how long an ejected endpoint is skipped.
+    pub fn ejection_duration(mut self, duration: Duration) -> Self {
+        self.config.ejection_duration = duration;
+        self
+    }
+
+    /// Builds the `BalanceConfig`.
+    pub fn build(self) -> BalanceConfig {
+        self.config
+    }
+}
+
+/// The base URLs of a client, with their load and health.
+pub(crate) struct Endpoints {
+    config: BalanceConfig,
+    endpoints: Vec<Endpoint>,
+    next: AtomicUsize,
+}
+
+struct Endpoint {
+    url: Url,
+    outstanding: AtomicUsize,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    consecutive_failures: u32,
+    ejected_until: Option<Instant>,
+}
+
+impl Endpoint {
+    fn is_ejected(&self, now: Instant) -> bool {
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        health.ejected_until.is_some_and(|until| until > now)
+    }
+
+    fn outstanding(&self) -> usize {
+        self.outstanding.load(Ordering::Relaxed)
+    }
+}
+
+impl Endpoints {
+    /// Creates the endpoints for a non-empty list of base URLs.
+    pub(crate) fn new(urls: Vec<Url>, config: BalanceConfig) -> Self {
+        debug_assert!(!urls.is_empty());
+        Self {
+            config,
+            endpoints: urls
+                .into_iter()
+                .map(|url| Endpoint {
+                    url,
+                    outstanding: AtomicUsize::new(0),
+                    health: Mutex::default(),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the first base URL, which identifies the service as a whole.
+    pub(crate) fn primary(&self) -> &Url {
+        &self.endpoints[0].url
+    }
+
+    /// Picks an endpoint for an attempt, skipping ejected endpoints and those
+    /// in `avoid` where possible.
+    ///
+    /// The endpoint counts as having a request in flight until the returned
+    /// `Selected` is dropped.
+    pub(crate) fn select(&self, avoid: &[usize]) -> Selected<'_> {
+        let index = if self.endpoints.len() == 1 {
+            0
+        } else {
+            let now = Instant::now();
+            let healthy = (0..self.endpoints.len())
+                .filter(|i| !self.endpoints[*i].is_ejected(now))
+                .collect::<Vec<_>>();
+            let untried = healthy
+                .iter()
+                .copied()
+                .filter(|i| !avoid.contains(i))
+                .collect::<Vec<_>>();
+
+            // With every endpoint ejected, trying one beats failing outright
+            let candidates = if !untried.is_empty() {
+                untried
+            } else if !healthy.is_empty() {
+                healthy
+            } else {
+                (0..self.endpoints.len()).collect()
+            };
+            self.choose(&candidates)
+        };
+
+        let endpoint = &self.endpoints[index];
+        endpoint.outstanding.fetch_add(1, Ordering::Relaxed);
+        Selected {
+            index,
+            url: &endpoint.url,
+            outstanding: &endpoint.outstanding,
+        }
+    }
+
+    fn choose(&self, candidates: &[usize]) -> usize {
+        let outstanding = |i: &usize| self.endpoints[*i].outstanding();
+        match self.config.strategy {
+            BalanceStrategy::RoundRobin => {
+                candidates[self.next.fetch_add(1, Ordering::Relaxed) % candidates.len()]
+            }
+            BalanceStrategy::Random => {
+                candidates[rand::thread_rng().gen_range(0..candidates.len())]
+            }
+            BalanceStrategy::LeastOutstanding => {
+                // Start from a rotating offset so ties don't all go to the first endpoint
+                let start = self.next.fetch_add(1, Ordering::Relaxed);
+                (0..candidates.len())
+                    .map(|offset| candidates[(start + offset) % candidates.len()])
+                    .min_by_key(outstanding)
+                    .unwrap_or(candidates[0])
+            }
+            BalanceStrategy::PowerOfTwoChoices => {
+                if candidates.len() == 1 {
+                    return candidates[0];
+                }
+                let mut rng = rand::thread_rng();
+                let first = rng.gen_range(0..candidates.len());
+                let second = (first + rng.gen_range(1..candidates.len())) % candidates.len();
+                let (first, second) = (candidates[first], candidates[second]);
+                if outstanding(&second) < outstanding(&first) {
+                    second
+                } else {
+                    first
+                }
+            }
+        }
+    }
+
+    /// Records the outcome of an attempt against an endpoint, ejecting it once
+    /// it has failed too many times in a row.
+    pub(crate) fn record(&self, index: usize, healthy: bool) {
+        if self.endpoints.len() == 1 {
+            return;
+        }
+
+        let endpoint = &self.endpoints[index];
+        let mut health = endpoint.health.lock().unwrap_or_else(|e| e.into_inner());
+        if healthy {
+            *health = Health::default();
+            return;
+        }
+
+        health.consecutive_failures += 1;
+        if self.config.failure_threshold > 0
+            && health.consecutive_failures >= self.config.failure_threshold
+        {
+            // A duration too long to represent ejects the endpoint for good
+            let now = Instant::now();
+            health.ejected_until = Some(
+                now.checked_add(self.config.ejection_duration)
+                    .unwrap_or_else(|| now + FAR_FUTURE),
+            );
+            tracing::warn!(
+                endpoint = %endpoint.url,
+                failures = health.consecutive_failures,
+                ejection_ms = self.config.ejection_duration.as_millis(),
+                "Ejecting unhealthy endpoint"
+            );
+        }
+    }
+}
+
+/// An endpoint chosen for an attempt.
+pub(crate) struct Selected<'a> {
+    pub(crate) index: usize,
+    pub(crate) url: &'a Url,
+    outstanding: &'a AtomicUsize,
+}
+
+impl Drop for Selected<'_> {
+    fn drop(&mut self) {
+        self.outstanding.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints(count: usize, config: BalanceConfig) -> Endpoints {
+        let urls = (0..count)
+            .map(|i| Url::parse(&format!("http://host-{}.local", i)).unwrap())
+            .collect();
+        Endpoints::new(urls, config)
+    }
+
+    fn strategy(strategy: BalanceStrategy) -> BalanceConfig {
+        BalanceConfig::builder().strategy(strategy).build()
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let endpoints = endpoints(3, BalanceConfig::default());
+        let picks = (0..6)
+            .map(|_| endpoints.select(&[]).index)
+            .collect::<Vec<_>>();
+        assert_eq!(picks, vec![0, 1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_least_outstanding() {
+        let endpoints = endpoints(3, strategy(BalanceStrategy::LeastOutstanding));
+        let first = endpoints.select(&[]);
+        let second = endpoints.select(&[]);
+        let third = endpoints.select(&[]);
+        let mut picked = vec![first.index, second.index, third.index];
+        picked.sort();
+        assert_eq!(picked, vec![0, 1, 2]);
+
+        // Only the released endpoint is idle
+        let released = second.index;
+        drop(second);
+        assert_eq!(endpoints.select(&[]).index, released);
+    }
+
+    #[test]
+    fn test_power_of_two_choices_avoids_busy_endpoint() {
+        let endpoints = endpoints(2, strategy(BalanceStrategy::PowerOfTwoChoices));
+        let busy = endpoints.select(&[]);
+        for _ in 0..10 {
+            assert_ne!(endpoints.select(&[]).index, busy.index);
+        }
+    }
+
+    #[test]
+    fn test_random_covers_all_endpoints() {
+        let endpoints = endpoints(3, strategy(BalanceStrategy::Random));
+        let mut seen = [false; 3];
+        for _ in 0..100 {
+            seen[endpoints.select(&[]).index] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+
+    #[test]
+    fn test_avoids_failed_endpoints() {
+        let endpoints = endpoints(3, strategy(BalanceStrategy::Random));
+        for _ in 0..20 {
+            assert_eq!(endpoints.select(&[0, 2]).index, 1);
+        }
+        // Avoiding everything falls back to any endpoint
+        endpoints.select(&[0, 1, 2]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ejection() {
+        let config = BalanceConfig::builder()
+            .strategy(BalanceStrategy::Random)
+            .failure_threshold(2)
+            .ejection_duration(Duration::from_secs(10))
+            .build();
+        let endpoints = endpoints(2, config);
+
+        endpoints.record(0, false);
+        assert!((0..20).any(|_| endpoints.select(&[]).index == 0));
+
+        endpoints.record(0, false);
+        assert!((0..20).all(|_| endpoints.select(&[]).index == 1));
+
+        // Ejected endpoints come back after the ejection duration
+        tokio::time::advance(Duration::from_secs(11)).await;
+        assert!((0..20).any(|_| endpoints.select(&[]).index == 0));
+
+        // Every endpoint ejected still leaves something to try
+        endpoints.record(0, false);
+        endpoints.record(1, false);
+        endpoints.record(1, false);
+        endpoints.select(&[]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_huge_ejection_duration() {
+        let config = BalanceConfig::builder()
+            .strategy(BalanceStrategy::Random)
+            .failure_threshold(1)
+            .ejection_duration(Duration::MAX)
+            .build();
+        let endpoints = endpoints(2, config);
+
+        endpoints.record(0, false);
+        assert!((0..20).all(|_| endpoints.select(&[]).index == 1));
+    }
+}
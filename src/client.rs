@@ -4,14 +4,34 @@
 //! Use [`ClientBuilder`] to configure and create clients.
 
 use crate::{
+    balance::{BalanceConfig, Endpoints},
+    compression::CompressionConfig,
+    connection::ConnectionConfig,
+    cookie::CookieJar,
+    dns::{ReqwestResolver, Resolve},
+    hedge::{HedgeConfig, LatencyTracker},
+    logging::{log_at, LogConfig},
     metadata::RequestMetadata,
-    rate_limit::RateLimitConfig,
+    metrics::{MetricLabels, MetricsRecorder},
+    pagination::PaginationConfig,
+    rate_limit::{RateLimitConfig, RateLimitGate, RateLimitGates},
+    redact::{self, RedactionPolicy},
+    redirect::{self, RedirectPolicy},
     retry::{RetryOnRetryable, RetryPredicate, RetryStrategy},
-    Error, Response, Result,
+    signing::RequestSigner,
+    tls::TlsConfig,
+    transport::{BodyReadTime, ReqwestTransport, Transport},
+    Attempt, Error, RequestSummary, Response, Result, Timings,
 };
-use http::{HeaderMap, HeaderName, HeaderValue, Method};
+use either::Either;
+use futures_util::future;
+use futures_util::stream::{self, FuturesUnordered, Stream, StreamExt};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
 use serde::{de::DeserializeOwned, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use url::Url;
 
@@ -72,13 +92,25 @@ pub struct Client {
 }
 
 struct ClientInner {
-    http_client: reqwest::Client,
-    base_url: Url,
+    transport: Box<dyn Transport>,
+    endpoints: Endpoints,
     default_headers: HeaderMap,
     retry_strategy: RetryStrategy,
     retry_predicate: Box<dyn RetryPredicate>,
     timeout: Option<Duration>,
     rate_limit_config: RateLimitConfig,
+    rate_limit_gates: RateLimitGates,
+    hedge_config: Option<HedgeConfig>,
+    latencies: LatencyTracker,
+    metrics_recorder: Option<Box<dyn MetricsRecorder>>,
+    redaction: RedactionPolicy,
+    log_config: LogConfig,
+    compression: CompressionConfig,
+    signer: Option<Box<dyn RequestSigner>>,
+    cookie_jar: Option<CookieJar>,
+    redirect_policy: RedirectPolicy,
+    #[cfg(feature = "opentelemetry")]
+    trace_propagation: crate::telemetry::TracePropagation,
 }
 
 impl Client {
@@ -105,6 +137,9 @@ impl Client {
     /// This is the main method for making requests. It handles serialization,
     /// retries, logging, and deserialization.
     ///
+    /// An empty response body is deserialized as JSON `null`, so `()` and
+    /// `Option<T>` can be used for endpoints that return no content.
+    ///
     /// # Type Parameters
     ///
     /// * `Req` - The request body type (must implement `Serialize`)
@@ -149,33 +184,545 @@ impl Client {
     where
         Req: Serialize,
         Res: DeserializeOwned,
+    {
+        self.call_with(metadata, body, |_, body| serde_json::from_str(body), None)
+            .await
+    }
+
+    /// Makes a typed HTTP request to an API that uses non-2xx statuses for
+    /// domain results.
+    ///
+    /// 2xx responses are deserialized as `Res` and returned as [`Either::Left`].
+    /// Responses with a status accepted with [`RequestMetadata::accept_status`]
+    /// are deserialized as `ErrBody` and returned as [`Either::Right`]. Any other
+    /// status is an [`Error::HttpError`], as with [`call`](Self::call).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, Either, metadata::RequestMetadata};
+    /// use http::Method;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Transfer { amount: u64 }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Receipt { id: String }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Declined { reason: String }
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .build()?;
+    ///
+    /// let metadata = RequestMetadata::new(Method::POST, "/transfers").accept_status(402);
+    /// let response = client
+    ///     .call_either::<_, Receipt, Declined>(metadata, Some(&Transfer { amount: 100 }))
+    ///     .await?;
+    /// match response.data {
+    ///     Either::Left(receipt) => println!("Transfer {}", receipt.id),
+    ///     Either::Right(declined) => println!("Declined: {}", declined.reason),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call_either<Req, Res, ErrBody>(
+        &self,
+        metadata: RequestMetadata,
+        body: Option<&Req>,
+    ) -> Result<Response<Either<Res, ErrBody>>>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+        ErrBody: DeserializeOwned,
+    {
+        self.call_with(
+            metadata,
+            body,
+            |status, body| {
+                if status.is_success() {
+                    serde_json::from_str(body).map(Either::Left)
+                } else {
+                    serde_json::from_str(body).map(Either::Right)
+                }
+            },
+            None,
+        )
+        .await
+    }
+
+    /// Fetches every page of a paginated endpoint, yielding the items of all
+    /// pages as a single stream.
+    ///
+    /// Pages are fetched lazily, one at a time, as the stream is consumed. Each
+    /// page is fetched with [`call`](Self::call), so retries and rate limiting
+    /// apply to every page. If a page can't be fetched, its error is yielded and
+    /// the stream ends; items that fail to deserialize are yielded as
+    /// [`Error::DeserializationFailed`] without ending the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, metadata::RequestMetadata};
+    /// use calleen::pagination::{PageScheme, PaginationConfig};
+    /// use futures_util::StreamExt;
+    /// use http::Method;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Repo { name: String }
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.github.com")?
+    ///     .build()?;
+    ///
+    /// let metadata = RequestMetadata::new(Method::GET, "/orgs/rust-lang/repos");
+    /// let config = PaginationConfig::builder()
+    ///     .scheme(PageScheme::Link)
+    ///     .max_pages(10)
+    ///     .build();
+    ///
+    /// let mut repos = std::pin::pin!(client.paginate::<Repo>(metadata, config));
+    /// while let Some(repo) = repos.next().await {
+    ///     println!("{}", repo?.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn paginate<'a, T>(
+        &'a self,
+        metadata: RequestMetadata,
+        config: PaginationConfig,
+    ) -> impl Stream<Item = Result<T>> + 'a
+    where
+        T: DeserializeOwned + 'a,
+    {
+        let first = config.first_page(metadata);
+
+        stream::unfold(
+            (config, Some(first), 0),
+            move |(config, next, pages)| async move {
+                let metadata = next?;
+                if pages >= config.max_pages {
+                    tracing::debug!(
+                        max_pages = config.max_pages,
+                        "Reached maximum number of pages"
+                    );
+                    return None;
+                }
+
+                let url = self.build_url(&metadata);
+                let page = match self
+                    .call::<(), serde_json::Value>(metadata.clone(), None)
+                    .await
+                {
+                    Ok(page) => page,
+                    Err(e) => return Some((vec![Err(e)], (config, None, pages + 1))),
+                };
+
+                let Some(items) = config.items(&page.data) else {
+                    let error = Error::DeserializationFailed {
+                        raw_response: self.inner.redaction.log_body(&page.raw_body).into_owned(),
+                        serde_error: "response body has no array of items".to_string(),
+                        status: page.status,
+                        request: page.request.clone().map(Box::new),
+                    };
+                    return Some((vec![Err(error)], (config, None, pages + 1)));
+                };
+
+                let next = config.next_page(metadata, &url, &page, items.len());
+                let items = items
+                    .iter()
+                    .map(|item| {
+                        serde::Deserialize::deserialize(item).map_err(|e: serde_json::Error| {
+                            Error::DeserializationFailed {
+                                raw_response: self
+                                    .inner
+                                    .redaction
+                                    .log_body(&item.to_string())
+                                    .into_owned(),
+                                serde_error: e.to_string(),
+                                status: page.status,
+                                request: page.request.clone().map(Box::new),
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                Some((items, (config, next, pages + 1)))
+            },
+        )
+        .flat_map(stream::iter)
+    }
+
+    /// Returns when requests in a rate limit bucket will be unblocked, or `None`
+    /// if the bucket isn't currently rate limited.
+    ///
+    /// See [`RateLimitScope`](crate::rate_limit::RateLimitScope) for how buckets
+    /// are keyed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    ///
+    /// # fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .build()?;
+    ///
+    /// if let Some(until) = client.rate_limited_until("api.example.com") {
+    ///     println!("Rate limited for another {:?}", until - std::time::Instant::now());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rate_limited_until(&self, bucket: &str) -> Option<std::time::Instant> {
+        self.inner
+            .rate_limit_gates
+            .blocked_until(bucket)
+            .map(|until| until.into_std())
+    }
+
+    /// Returns every currently rate limited bucket, with when requests in it will
+    /// be unblocked.
+    pub fn rate_limited_buckets(&self) -> std::collections::HashMap<String, std::time::Instant> {
+        self.inner
+            .rate_limit_gates
+            .blocked()
+            .into_iter()
+            .map(|(bucket, until)| (bucket, until.into_std()))
+            .collect()
+    }
+
+    /// Makes many typed HTTP requests concurrently, yielding the results in the
+    /// order of `requests`.
+    ///
+    /// At most `concurrency` requests are in flight at a time. Every request is
+    /// made as with [`call`](Self::call), including retries. When one request in
+    /// the batch is rate limited, the whole batch pauses until the rate limit
+    /// resets, instead of every request waiting and retrying independently.
+    ///
+    /// Collect the stream to wait for every result, or use
+    /// [`try_call_many`](Self::try_call_many) to stop on the first error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, metadata::RequestMetadata};
+    /// use futures_util::StreamExt;
+    /// use http::Method;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User { name: String }
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .build()?;
+    ///
+    /// let requests = (1..=1000)
+    ///     .map(|id| (RequestMetadata::new(Method::GET, format!("/users/{}", id)), None::<()>));
+    ///
+    /// let results: Vec<_> = client.call_many::<_, User, _>(requests, 16).collect().await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_many<'a, Req, Res, I>(
+        &'a self,
+        requests: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<Response<Res>>> + 'a
+    where
+        I: IntoIterator<Item = (RequestMetadata, Option<Req>)>,
+        I::IntoIter: 'a,
+        Req: Serialize + 'a,
+        Res: DeserializeOwned + 'a,
+    {
+        self.batch(requests)
+            .map(|(_, call)| call)
+            .buffered(concurrency.max(1))
+    }
+
+    /// Makes many typed HTTP requests concurrently, yielding the results as they
+    /// complete.
+    ///
+    /// Each result is paired with the index of its request in `requests`. See
+    /// [`call_many`](Self::call_many) for how concurrency and rate limits are
+    /// handled.
+    pub fn call_many_unordered<'a, Req, Res, I>(
+        &'a self,
+        requests: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = (usize, Result<Response<Res>>)> + 'a
+    where
+        I: IntoIterator<Item = (RequestMetadata, Option<Req>)>,
+        I::IntoIter: 'a,
+        Req: Serialize + 'a,
+        Res: DeserializeOwned + 'a,
+    {
+        self.batch(requests)
+            .map(|(index, call)| async move { (index, call.await) })
+            .buffer_unordered(concurrency.max(1))
+    }
+
+    /// Makes many typed HTTP requests concurrently, stopping on the first error.
+    ///
+    /// Returns the responses in the order of `requests`. As soon as any request
+    /// fails, no more requests are started, requests in flight are cancelled, and
+    /// the error is returned. See [`call_many`](Self::call_many) for how
+    /// concurrency and rate limits are handled.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of any request, in order of completion.
+    pub async fn try_call_many<Req, Res, I>(
+        &self,
+        requests: I,
+        concurrency: usize,
+    ) -> Result<Vec<Response<Res>>>
+    where
+        I: IntoIterator<Item = (RequestMetadata, Option<Req>)>,
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
+        let mut responses = Vec::new();
+        let mut results = std::pin::pin!(self.call_many_unordered(requests, concurrency));
+
+        while let Some((index, result)) = results.next().await {
+            if responses.len() <= index {
+                responses.resize_with(index + 1, || None);
+            }
+            responses[index] = Some(result?);
+        }
+
+        Ok(responses.into_iter().flatten().collect())
+    }
+
+    /// Returns a stream of the indexed, not yet started calls of a batch, sharing
+    /// a rate limit gate.
+    fn batch<'a, Req, Res, I>(
+        &'a self,
+        requests: I,
+    ) -> impl Stream<Item = (usize, impl Future<Output = Result<Response<Res>>> + 'a)> + 'a
+    where
+        I: IntoIterator<Item = (RequestMetadata, Option<Req>)>,
+        I::IntoIter: 'a,
+        Req: Serialize + 'a,
+        Res: DeserializeOwned + 'a,
+    {
+        let gate = Arc::new(RateLimitGate::default());
+
+        stream::iter(requests.into_iter().enumerate()).map(move |(index, (metadata, body))| {
+            let gate = gate.clone();
+            let call = async move {
+                self.call_with(
+                    metadata,
+                    body.as_ref(),
+                    |_, body| serde_json::from_str(body),
+                    Some(&gate),
+                )
+                .await
+            };
+            (index, call)
+        })
+    }
+
+    /// Makes a typed HTTP request, decoding successful and accepted responses
+    /// with `decode`.
+    ///
+    /// If a `gate` is given, it's shared in addition to the request's rate limit
+    /// bucket.
+    async fn call_with<Req, Res>(
+        &self,
+        metadata: RequestMetadata,
+        body: Option<&Req>,
+        decode: Decoder<Res>,
+        gate: Option<&RateLimitGate>,
+    ) -> Result<Response<Res>>
+    where
+        Req: Serialize,
+    {
+        let start_time = Instant::now();
+
+        #[cfg(feature = "opentelemetry")]
+        let result = {
+            use tracing::Instrument;
+
+            let span = crate::telemetry::call_span(&metadata.method, &self.build_url(&metadata));
+            let result = self
+                .call_with_retries(&metadata, body, decode, gate)
+                .instrument(span.clone())
+                .await;
+            crate::telemetry::record_result(&span, &result);
+            result
+        };
+
+        #[cfg(not(feature = "opentelemetry"))]
+        let result = self.call_with_retries(&metadata, body, decode, gate).await;
+
+        if let Some(metrics) = &self.inner.metrics_recorder {
+            let status = match &result {
+                Ok(response) => Some(response.status),
+                Err(Error::MaxRetriesExceeded { last_error, .. }) => last_error.status(),
+                Err(e) => e.status(),
+            };
+            metrics.record_call(&MetricLabels::from(&metadata), status, start_time.elapsed());
+        }
+
+        result
+    }
+
+    /// Makes a typed HTTP request, retrying failed attempts according to the
+    /// client's retry strategy.
+    async fn call_with_retries<Req, Res>(
+        &self,
+        metadata: &RequestMetadata,
+        body: Option<&Req>,
+        decode: Decoder<Res>,
+        gate: Option<&RateLimitGate>,
+    ) -> Result<Response<Res>>
+    where
+        Req: Serialize,
     {
         let start_time = Instant::now();
+        let labels = MetricLabels::from(metadata);
         let mut attempt = 0;
+        let mut hedges = 0;
         let mut last_error = None;
+        let mut attempt_history = Vec::new();
+        let mut backoff = Duration::ZERO;
+        // Retries fail over to endpoints this request hasn't failed on
+        let failed_endpoints = Mutex::new(Vec::new());
+
+        // Attempts wait while any of the request's gates is blocked, and rate
+        // limited responses block all of them
+        let shared_gate = if self.inner.rate_limit_config.enabled {
+            self.inner.rate_limit_gates.for_request(
+                &self.inner.rate_limit_config,
+                metadata,
+                &self.build_url(metadata),
+            )
+        } else {
+            None
+        };
+        let gates: Vec<&RateLimitGate> = gate.into_iter().chain(shared_gate.as_deref()).collect();
 
         loop {
             attempt += 1;
 
-            let result = match self.execute_request(&metadata, body, attempt).await {
-                Ok(response) => {
+            let mut waited = Duration::ZERO;
+            let mut gate_error = None;
+            for gate in &gates {
+                if let Some(remaining) = gate.remaining() {
+                    // Fails without sending if the shared rate limit exceeds the max wait
+                    if let Err(e) = self.inner.rate_limit_config.apply_max_wait(remaining) {
+                        gate_error = Some(e);
+                        break;
+                    }
+                }
+                waited += gate.wait().await.unwrap_or_default();
+            }
+            if !waited.is_zero() {
+                tracing::debug!(
+                    waited_ms = waited.as_millis(),
+                    attempt = attempt,
+                    "Waited for shared rate limit"
+                );
+                if let Some(metrics) = &self.inner.metrics_recorder {
+                    metrics.record_rate_limit_wait(&labels, waited);
+                }
+            }
+
+            let attempt_start = Instant::now();
+            let mut timings = Timings {
+                waited: std::mem::take(&mut backoff) + waited,
+                ..Timings::default()
+            };
+
+            let sent = match gate_error {
+                Some(e) => Err((e, 0)),
+                None => {
+                    self.execute_hedged(metadata, body, attempt, &failed_endpoints)
+                        .await
+                }
+            };
+            let result = match sent {
+                Ok((response, hedges_sent)) => {
+                    hedges += hedges_sent;
                     let latency = start_time.elapsed();
-                    self.parse_response(response, latency, attempt).await
+                    self.parse_response(metadata, response, latency, attempt, decode, &mut timings)
+                        .map(|mut response| {
+                            response.hedges = hedges;
+                            response
+                        })
                 }
-                Err(e) => Err(e),
+                Err((e, hedges_sent)) => {
+                    hedges += hedges_sent;
+                    Err(e)
+                }
+            };
+
+            timings.total = attempt_start.elapsed();
+            let status = match &result {
+                Ok(response) => Some(response.status),
+                Err(e) => e.status(),
             };
+            if let Some(metrics) = &self.inner.metrics_recorder {
+                metrics.record_attempt(&labels, status, timings.total);
+                if let Err(Error::DeserializationFailed { status, .. }) = &result {
+                    metrics.record_deserialization_failure(&labels, *status);
+                }
+            }
+            attempt_history.push(Attempt {
+                number: attempt,
+                status,
+                error: result
+                    .as_ref()
+                    .err()
+                    .map(|e| self.inner.redaction.truncate(&e.to_string()).into_owned()),
+                timings,
+            });
 
             match result {
-                Ok(response) => return Ok(response),
+                Ok(mut response) => {
+                    response.timings = timings;
+                    response.attempt_history = attempt_history;
+                    return Ok(response);
+                }
                 Err(e) => {
-                    tracing::warn!(
-                        error = %e,
+                    log_at!(
+                        self.inner.log_config.attempt_failure_level(metadata, &e),
+                        error = %self.inner.redaction.truncate(&e.to_string()),
                         attempt = attempt,
                         method = %metadata.method,
                         path = %metadata.path,
                         "Request failed"
                     );
 
+                    // Pause every request sharing a gate, not just this one
+                    let rate_limit_delay = match self.inner.rate_limit_config.delay_for(&e) {
+                        Ok(delay) => delay,
+                        Err(error) => {
+                            if let Error::RateLimited { retry_after, .. } = &error {
+                                for gate in &gates {
+                                    gate.block_for(*retry_after);
+                                }
+                            }
+                            return Err(error);
+                        }
+                    };
+                    if let Some(delay) = rate_limit_delay {
+                        for gate in &gates {
+                            gate.block_for(delay);
+                        }
+                    }
+
                     // Check if we should retry
                     if !self.inner.retry_predicate.should_retry(&e, attempt) {
                         return Err(e);
@@ -186,21 +733,17 @@ impl Client {
                     let delay = match self.inner.retry_strategy.delay_for_attempt(attempt) {
                         Some(normal_delay) => {
                             // We have retries remaining - check if rate limit delay should override
-                            if self.inner.rate_limit_config.enabled {
-                                if let Some(rate_limit_delay) =
-                                    e.rate_limit_delay(self.inner.rate_limit_config.max_wait)
-                                {
-                                    tracing::info!(
-                                        rate_limit_delay_ms = rate_limit_delay.as_millis(),
-                                        attempt = attempt,
-                                        max_wait_secs =
-                                            self.inner.rate_limit_config.max_wait.as_secs(),
-                                        "Rate limited - waiting before retry"
-                                    );
-                                    Some(rate_limit_delay)
-                                } else {
-                                    Some(normal_delay)
+                            if let Some(rate_limit_delay) = rate_limit_delay {
+                                tracing::info!(
+                                    rate_limit_delay_ms = rate_limit_delay.as_millis(),
+                                    attempt = attempt,
+                                    max_wait_secs = self.inner.rate_limit_config.max_wait.as_secs(),
+                                    "Rate limited - waiting before retry"
+                                );
+                                if let Some(metrics) = &self.inner.metrics_recorder {
+                                    metrics.record_rate_limit_wait(&labels, rate_limit_delay);
                                 }
+                                Some(rate_limit_delay)
                             } else {
                                 Some(normal_delay)
                             }
@@ -218,13 +761,19 @@ impl Client {
                             );
                         }
 
+                        if let Some(metrics) = &self.inner.metrics_recorder {
+                            metrics.record_retry(&labels, attempt);
+                        }
+
                         tokio::time::sleep(delay).await;
+                        backoff = delay;
                         last_error = Some(e);
                     } else {
                         // No more retries
                         return Err(Error::MaxRetriesExceeded {
                             attempts: attempt,
                             last_error: Box::new(last_error.unwrap_or(e)),
+                            attempt_history,
                         });
                     }
                 }
@@ -232,90 +781,405 @@ impl Client {
         }
     }
 
-    /// Executes a single request attempt.
-    async fn execute_request<Req>(
+    /// Executes a single request attempt, hedging it if configured.
+    ///
+    /// Returns the first response received along with the number of hedged
+    /// requests that were sent. Requests still in flight are cancelled.
+    async fn execute_hedged<Req>(
         &self,
         metadata: &RequestMetadata,
         body: Option<&Req>,
         attempt: usize,
-    ) -> Result<reqwest::Response>
+        failed_endpoints: &Mutex<Vec<usize>>,
+    ) -> std::result::Result<(http::Response<Vec<u8>>, usize), (Error, usize)>
     where
         Req: Serialize,
     {
-        // Build the full URL
-        let mut url = self.inner.base_url.clone();
-        url.set_path(&metadata.path);
+        let hedge_config = match &self.inner.hedge_config {
+            Some(config) if metadata.method.is_idempotent() => config,
+            _ => {
+                return self
+                    .execute_request(metadata, body, attempt, failed_endpoints)
+                    .await
+                    .map(|response| (response, 0))
+                    .map_err(|e| (e, 0));
+            }
+        };
 
-        // Add query parameters
-        for (key, value) in &metadata.query_params {
-            url.query_pairs_mut().append_pair(key, value);
-        }
+        // Latencies are measured on tokio's clock, like the hedge delay itself
+        let delay = hedge_config.delay(&self.inner.latencies);
+        let timed_request = |hedge: usize| async move {
+            let sent_at = tokio::time::Instant::now();
+            let result = self
+                .execute_request(metadata, body, attempt, failed_endpoints)
+                .await;
+            (result, sent_at.elapsed(), hedge)
+        };
 
-        tracing::debug!(
-            method = %metadata.method,
-            url = %url,
-            attempt = attempt,
-            "Executing HTTP request"
-        );
+        let started = tokio::time::Instant::now();
+        let mut in_flight = FuturesUnordered::new();
+        in_flight.push(timed_request(0));
+        let mut hedges = 0;
+        let mut original_pending = true;
 
-        // Build the request
-        let mut request = self.inner.http_client.request(metadata.method.clone(), url);
+        loop {
+            let next = if hedges < hedge_config.max_hedges {
+                match future::select(in_flight.next(), Box::pin(tokio::time::sleep(delay))).await {
+                    future::Either::Left((next, _)) => next,
+                    future::Either::Right(_) => {
+                        hedges += 1;
+                        tracing::debug!(
+                            hedge = hedges,
+                            delay_ms = delay.as_millis(),
+                            attempt = attempt,
+                            "Sending hedged request"
+                        );
+                        in_flight.push(timed_request(hedges));
+                        continue;
+                    }
+                }
+            } else {
+                in_flight.next().await
+            };
 
-        // Add default headers
-        for (name, value) in &self.inner.default_headers {
-            request = request.header(name, value);
-        }
+            // At least one request is always in flight at this point
+            let Some((result, latency, hedge)) = next else {
+                unreachable!("hedged requests exhausted without a result");
+            };
 
-        // Add request-specific headers
-        for (name, value) in &metadata.headers {
-            request = request.header(name, value);
-        }
+            if hedge == 0 {
+                original_pending = false;
+                if result.is_ok() {
+                    self.inner.latencies.record(latency);
+                }
+            }
 
-        // Add timeout if configured
-        if let Some(timeout) = self.inner.timeout {
-            request = request.timeout(timeout);
+            match result {
+                Ok(response) => {
+                    if hedge > 0 {
+                        // The original request is cancelled, but it took at least
+                        // this long. Leaving it out would skew the percentile low.
+                        if original_pending {
+                            self.inner.latencies.record(started.elapsed());
+                        }
+                        tracing::debug!(hedge = hedge, "Hedged request won");
+                    }
+                    return Ok((response, hedges));
+                }
+                // Wait for the remaining requests, unless there are none left
+                Err(e) if in_flight.is_empty() => return Err((e, hedges)),
+                Err(e) => {
+                    tracing::debug!(
+                        error = %self.inner.redaction.truncate(&e.to_string()),
+                        hedge = hedge,
+                        "Hedged request failed"
+                    );
+                }
+            }
         }
+    }
 
-        // Add body if provided
-        if let Some(body) = body {
-            let json = serde_json::to_value(body)
-                .map_err(|e| Error::SerializationFailed(e.to_string()))?;
-            request = request.json(&json);
-        }
+    /// Builds the full URL for a request on the primary base URL.
+    ///
+    /// Attempts are sent to whichever endpoint is selected for them, but the
+    /// primary one identifies the request for tracing and rate limiting.
+    fn build_url(&self, metadata: &RequestMetadata) -> Url {
+        endpoint_url(self.inner.endpoints.primary(), metadata)
+    }
 
-        // Execute the request
-        let response = request.send().await?;
+    /// Executes a single request attempt against the next endpoint, recording
+    /// its health.
+    ///
+    /// Endpoints that fail are added to `failed_endpoints` so that retries
+    /// avoid them.
+    async fn execute_request<Req>(
+        &self,
+        metadata: &RequestMetadata,
+        body: Option<&Req>,
+        attempt: usize,
+        failed_endpoints: &Mutex<Vec<usize>>,
+    ) -> Result<http::Response<Vec<u8>>>
+    where
+        Req: Serialize,
+    {
+        let endpoint = self
+            .inner
+            .endpoints
+            .select(&failed_endpoints.lock().unwrap_or_else(|e| e.into_inner()));
+        let result = self.execute_on(metadata, endpoint.url, body, attempt).await;
 
-        Ok(response)
+        let healthy = match &result {
+            Ok(response) => Some(!response.status().is_server_error()),
+            Err(Error::Network(_) | Error::Timeout { .. }) => Some(false),
+            Err(_) => None,
+        };
+        if let Some(healthy) = healthy {
+            self.inner.endpoints.record(endpoint.index, healthy);
+            if !healthy {
+                failed_endpoints
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(endpoint.index);
+            }
+        }
+        result
     }
 
-    /// Parses the response and returns a typed `Response`.
-    async fn parse_response<Res>(
+    /// Executes a single request attempt against the given base URL.
+    async fn execute_on<Req>(
         &self,
-        response: reqwest::Response,
-        latency: Duration,
-        attempts: usize,
-    ) -> Result<Response<Res>>
+        metadata: &RequestMetadata,
+        base_url: &Url,
+        body: Option<&Req>,
+        attempt: usize,
+    ) -> Result<http::Response<Vec<u8>>>
     where
-        Res: DeserializeOwned,
+        Req: Serialize,
     {
-        let status = response.status();
-        let headers = response.headers().clone();
-
-        tracing::info!(
-            status = status.as_u16(),
-            latency_ms = latency.as_millis(),
-            attempts = attempts,
-            "Received HTTP response"
-        );
+        let url = endpoint_url(base_url, metadata);
 
-        // Check for HTTP errors (non-2xx)
-        if !status.is_success() {
-            let raw_response = response.text().await.unwrap_or_default();
+        #[cfg(feature = "opentelemetry")]
+        {
+            use tracing::Instrument;
+
+            let span = crate::telemetry::attempt_span(&metadata.method, &url, attempt);
+            let result = self
+                .send_request(metadata, url, body, attempt)
+                .instrument(span.clone())
+                .await;
+            match &result {
+                Ok(response) => crate::telemetry::record_status(&span, response.status()),
+                Err(e) => crate::telemetry::record_error(&span, e),
+            }
+            result
+        }
+
+        #[cfg(not(feature = "opentelemetry"))]
+        self.send_request(metadata, url, body, attempt).await
+    }
+
+    /// Sends a single HTTP request to the given URL.
+    async fn send_request<Req>(
+        &self,
+        metadata: &RequestMetadata,
+        url: Url,
+        body: Option<&Req>,
+        attempt: usize,
+    ) -> Result<http::Response<Vec<u8>>>
+    where
+        Req: Serialize,
+    {
+        tracing::debug!(
+            method = %metadata.method,
+            url = %url,
+            attempt = attempt,
+            "Executing HTTP request"
+        );
+
+        // Add default headers, then request-specific headers
+        let mut headers = HeaderMap::new();
+        for (name, value) in self.inner.default_headers.iter().chain(&metadata.headers) {
+            headers.append(name, value.clone());
+        }
+
+        // Propagate the trace context of the current attempt
+        #[cfg(feature = "opentelemetry")]
+        {
+            let mut trace_headers = HeaderMap::new();
+            self.inner.trace_propagation.inject(&mut trace_headers);
+            headers.extend(trace_headers);
+        }
+
+        // Add body if provided
+        let body = match body {
+            Some(body) => {
+                let json = serde_json::to_value(body)
+                    .map_err(|e| Error::SerializationFailed(e.to_string()))?
+                    .to_string();
+                if self.inner.log_config.log_request_body {
+                    tracing::debug!(body = %self.inner.redaction.log_body(&json), "Request body");
+                }
+                headers
+                    .entry(http::header::CONTENT_TYPE)
+                    .or_insert(HeaderValue::from_static("application/json"));
+                self.inner
+                    .compression
+                    .compress_request(&mut headers, json.into_bytes())?
+            }
+            None => Vec::new(),
+        };
+        self.inner.compression.add_accept_encoding(&mut headers);
+
+        // Execute the request and its redirects, timing out if configured
+        let sent_at = Instant::now();
+        let mut sent = None;
+        let response =
+            self.follow_redirects(metadata.method.clone(), url, headers, body, &mut sent);
+        let mut response = match self.inner.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, response).await {
+                Ok(response) => response,
+                Err(_) => {
+                    return Err(Error::Timeout {
+                        request: sent.map(Box::new),
+                    })
+                }
+            },
+            None => response.await,
+        }?;
+
+        let body_read = response
+            .extensions_mut()
+            .remove::<BodyReadTime>()
+            .map(|BodyReadTime(time)| time)
+            .unwrap_or_default();
+        let headers = sent_at.elapsed().saturating_sub(body_read);
+        let decompress_start = Instant::now();
+        let mut response = self.inner.compression.decompress_response(response)?;
+        response.extensions_mut().insert(Timings {
+            headers,
+            body: body_read + decompress_start.elapsed(),
+            ..Timings::default()
+        });
+        Ok(response)
+    }
+
+    /// Sends a request, following redirects according to the redirect policy.
+    ///
+    /// The returned response carries the [`RequestSummary`] of the last request
+    /// in its extensions. `sent` is set to the summary of each request as it's
+    /// sent, so it's available if the exchange times out.
+    async fn follow_redirects(
+        &self,
+        mut method: Method,
+        mut url: Url,
+        mut headers: HeaderMap,
+        mut body: Vec<u8>,
+        sent: &mut Option<RequestSummary>,
+    ) -> Result<http::Response<Vec<u8>>> {
+        let policy = &self.inner.redirect_policy;
+        let mut redirects = Vec::new();
+        // Cleared once credentials are stripped from the chain
+        let mut sign = true;
+
+        loop {
+            let mut request_headers = headers.clone();
+            if let Some(jar) = &self.inner.cookie_jar {
+                jar.add_cookie_header(&url, &mut request_headers);
+            }
+
+            let mut request = http::Request::new(body.clone());
+            *request.method_mut() = method.clone();
+            *request.uri_mut() = url
+                .as_str()
+                .parse()
+                .map_err(|e| Error::ConfigurationError(format!("Invalid request URL: {}", e)))?;
+            *request.headers_mut() = request_headers;
+
+            // Sign last, so the signature covers the request exactly as it's sent
+            if let Some(signer) = self.inner.signer.as_ref().filter(|_| sign) {
+                signer.sign(&mut request)?;
+            }
+
+            let summary = RequestSummary {
+                method: method.clone(),
+                url: redact::redact_url(&url),
+                redirects: redirects.iter().map(redact::redact_url).collect(),
+                headers: self.inner.redaction.redact_headers(request.headers()),
+                body_size: request.body().len(),
+            };
+            if self.inner.log_config.log_request_headers {
+                tracing::debug!(headers = ?summary.headers, "Request headers");
+            }
+            *sent = Some(summary.clone());
+            let mut response = match self.inner.transport.send(request).await {
+                Ok(response) => response,
+                Err(Error::Network(e)) => return Err(Error::Network(e.with_request(summary))),
+                Err(Error::Timeout { .. }) => {
+                    return Err(Error::Timeout {
+                        request: Some(Box::new(summary)),
+                    })
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(jar) = &self.inner.cookie_jar {
+                jar.store_response(&url, response.headers());
+            }
+
+            let status = response.status();
+            let Some(next) = policy.next_url(&url, status, response.headers(), redirects.len())
+            else {
+                response.extensions_mut().insert(summary);
+                response
+                    .extensions_mut()
+                    .insert(RedirectChain { url, redirects });
+                return Ok(response);
+            };
+
+            tracing::debug!(
+                status = status.as_u16(),
+                from = %url,
+                to = %next,
+                "Following redirect"
+            );
+            if redirect::changes_to_get(status, &method) {
+                method = Method::GET;
+                body = Vec::new();
+                for name in [
+                    http::header::CONTENT_TYPE,
+                    http::header::CONTENT_ENCODING,
+                    http::header::CONTENT_LENGTH,
+                ] {
+                    headers.remove(name);
+                }
+            }
+            if policy.strip_credentials(&url, &next, &mut headers) {
+                sign = false;
+            }
+            redirects.push(std::mem::replace(&mut url, next));
+        }
+    }
+
+    /// Parses the response and returns a typed `Response`.
+    fn parse_response<Res>(
+        &self,
+        metadata: &RequestMetadata,
+        response: http::Response<Vec<u8>>,
+        latency: Duration,
+        attempts: usize,
+        decode: Decoder<Res>,
+        timings: &mut Timings,
+    ) -> Result<Response<Res>> {
+        let (mut parts, body) = response.into_parts();
+        let status = parts.status;
+        let request = parts.extensions.remove::<RequestSummary>();
+        let chain = parts.extensions.remove::<RedirectChain>();
+        if let Some(transfer) = parts.extensions.remove::<Timings>() {
+            timings.headers = transfer.headers;
+            timings.body = transfer.body;
+        }
+        let headers = parts.headers;
+        // Decoded as UTF-8 like reqwest's `text()` without its `charset` feature,
+        // which this crate never enabled; a `Content-Type` charset is ignored
+        let raw_body = String::from_utf8_lossy(&body).into_owned();
+
+        log_at!(
+            self.inner.log_config.response_level(metadata, status),
+            status = status.as_u16(),
+            latency_ms = latency.as_millis(),
+            attempts = attempts,
+            "Received HTTP response"
+        );
+
+        // Check for HTTP errors (non-2xx), unless the request accepts the status
+        if !status.is_success() && !metadata.accepted_statuses.contains(&status.as_u16()) {
+            let raw_response = raw_body;
 
             // Parse rate limit info if enabled
             let rate_limit_info = if self.inner.rate_limit_config.enabled {
-                let info = crate::rate_limit::RateLimitInfo::from_headers(&headers);
+                let info = crate::rate_limit::RateLimitInfo::parse(
+                    &headers,
+                    &self.inner.rate_limit_config.dialect,
+                );
                 if info.is_rate_limited() {
                     Some(info)
                 } else {
@@ -325,47 +1189,71 @@ impl Client {
                 None
             };
 
+            let level = self.inner.log_config.error_status_level(metadata, status);
             if status.is_client_error() {
-                tracing::error!(
+                log_at!(
+                    level,
                     status = status.as_u16(),
-                    response = %raw_response,
+                    response = %self.inner.redaction.log_body(&raw_response),
                     "Client error (4xx)"
                 );
             } else if status.is_server_error() {
-                tracing::warn!(
+                log_at!(
+                    level,
                     status = status.as_u16(),
-                    response = %raw_response,
+                    response = %self.inner.redaction.log_body(&raw_response),
                     "Server error (5xx)"
                 );
             }
 
             return Err(Error::HttpError {
                 status,
-                raw_response: raw_response.into_boxed_str(),
-                headers: Box::new(headers),
+                raw_response: self
+                    .inner
+                    .redaction
+                    .log_body(&raw_response)
+                    .into_owned()
+                    .into_boxed_str(),
+                headers: Box::new(self.inner.redaction.redact_headers(&headers)),
                 rate_limit_info,
+                request: request.map(Box::new),
             });
         }
 
-        // Get raw response text
-        let raw_body = response.text().await?;
-
-        // Try to deserialize
-        match serde_json::from_str::<Res>(&raw_body) {
-            Ok(data) => Ok(Response::new(
-                data, raw_body, status, headers, latency, attempts,
-            )),
+        // Try to deserialize, treating an empty body (e.g. 204 No Content or a
+        // HEAD response) as `null` so it decodes into `()` and `Option<T>`
+        let body = if raw_body.trim().is_empty() {
+            "null"
+        } else {
+            &raw_body
+        };
+        let deserialize_start = Instant::now();
+        let decoded = decode(status, body);
+        timings.deserialize = deserialize_start.elapsed();
+        match decoded {
+            Ok(data) => {
+                let mut response =
+                    Response::new(data, raw_body, status, headers, latency, attempts);
+                if let Some(chain) = chain {
+                    response.final_url = Some(chain.url);
+                    response.redirects = chain.redirects;
+                }
+                response.request = request;
+                Ok(response)
+            }
             Err(e) => {
-                tracing::error!(
+                log_at!(
+                    self.inner.log_config.deserialization_level(metadata, status),
                     error = %e,
-                    raw_response = %raw_body,
+                    raw_response = %self.inner.redaction.log_body(&raw_body),
                     "Failed to deserialize response"
                 );
 
                 Err(Error::DeserializationFailed {
-                    raw_response: raw_body,
+                    raw_response: self.inner.redaction.log_body(&raw_body).into_owned(),
                     serde_error: e.to_string(),
                     status,
+                    request: request.map(Box::new),
                 })
             }
         }
@@ -400,6 +1288,56 @@ impl Client {
         self.call::<(), Res>(metadata, None).await
     }
 
+    /// Makes a GET request to the specified path, returning `None` if the
+    /// resource doesn't exist.
+    ///
+    /// A 404 response is returned as `Ok(None)` instead of an
+    /// [`Error::HttpError`], and isn't logged as a client error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User { name: String }
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .build()?;
+    ///
+    /// match client.get_optional::<User>("/users/123").await? {
+    ///     Some(user) => println!("User: {}", user.data.name),
+    ///     None => println!("No such user"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_optional<Res>(&self, path: impl Into<String>) -> Result<Option<Response<Res>>>
+    where
+        Res: DeserializeOwned,
+    {
+        let metadata = RequestMetadata::new(Method::GET, path).accept_status(404);
+        let response = self
+            .call_with::<(), _>(
+                metadata,
+                None,
+                |status, body| {
+                    if status == StatusCode::NOT_FOUND {
+                        Ok(None)
+                    } else {
+                        serde_json::from_str(body).map(Some)
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(response.transpose())
+    }
+
     /// Makes a POST request to the specified path with a JSON body.
     ///
     /// # Examples
@@ -445,6 +1383,9 @@ impl Client {
     }
 
     /// Makes a DELETE request to the specified path.
+    ///
+    /// Use `()` or `Option<T>` as the response type for endpoints that may
+    /// respond with an empty body, such as `204 No Content`.
     pub async fn delete<Res>(&self, path: impl Into<String>) -> Result<Response<Res>>
     where
         Res: DeserializeOwned,
@@ -453,6 +1394,30 @@ impl Client {
         self.call::<(), Res>(metadata, None).await
     }
 
+    /// Makes a HEAD request to the specified path.
+    ///
+    /// HEAD responses have no body, so only the status and headers are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .build()?;
+    ///
+    /// let response = client.head("/files/report.pdf").await?;
+    /// println!("Size: {:?}", response.header("content-length"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn head(&self, path: impl Into<String>) -> Result<Response<()>> {
+        let metadata = RequestMetadata::new(Method::HEAD, path);
+        self.call::<(), ()>(metadata, None).await
+    }
+
     /// Makes a PATCH request to the specified path with a JSON body.
     pub async fn patch<Req, Res>(
         &self,
@@ -468,6 +1433,30 @@ impl Client {
     }
 }
 
+/// Decodes the body of a successful or accepted response, given its status.
+type Decoder<Res> = fn(StatusCode, &str) -> serde_json::Result<Res>;
+
+/// The unredacted URLs of a response's redirect chain, for
+/// [`Response::final_url`] and [`Response::redirects`].
+#[derive(Clone)]
+struct RedirectChain {
+    url: Url,
+    redirects: Vec<Url>,
+}
+
+/// Builds the full URL for a request from a base URL, path and query parameters.
+fn endpoint_url(base_url: &Url, metadata: &RequestMetadata) -> Url {
+    let mut url = base_url.clone();
+    url.set_path(&metadata.path);
+
+    // Add query parameters
+    for (key, value) in &metadata.query_params {
+        url.query_pairs_mut().append_pair(key, value);
+    }
+
+    url
+}
+
 /// Builder for configuring and creating a [`Client`].
 ///
 /// # Examples
@@ -492,24 +1481,62 @@ impl Client {
 /// # }
 /// ```
 pub struct ClientBuilder {
-    base_url: Option<Url>,
+    base_urls: Vec<Url>,
+    balance_config: BalanceConfig,
     default_headers: HeaderMap,
     retry_strategy: RetryStrategy,
     retry_predicate: Option<Box<dyn RetryPredicate>>,
     timeout: Option<Duration>,
     rate_limit_config: RateLimitConfig,
+    hedge_config: Option<HedgeConfig>,
+    metrics_recorder: Option<Box<dyn MetricsRecorder>>,
+    redaction: RedactionPolicy,
+    log_config: LogConfig,
+    compression: CompressionConfig,
+    signer: Option<Box<dyn RequestSigner>>,
+    cookie_jar: Option<CookieJar>,
+    redirect_policy: Option<RedirectPolicy>,
+    transport: Option<Box<dyn Transport>>,
+    connection_config: Option<ConnectionConfig>,
+    tls_config: Option<TlsConfig>,
+    dns_overrides: HashMap<String, Vec<SocketAddr>>,
+    dns_resolver: Option<Box<dyn Resolve>>,
+    reqwest_builder: Option<reqwest::ClientBuilder>,
+    #[cfg(unix)]
+    unix_socket: Option<std::path::PathBuf>,
+    #[cfg(feature = "opentelemetry")]
+    trace_propagation: crate::telemetry::TracePropagation,
 }
 
 impl ClientBuilder {
     /// Creates a new `ClientBuilder` with default settings.
     pub fn new() -> Self {
         Self {
-            base_url: None,
+            base_urls: Vec::new(),
+            balance_config: BalanceConfig::default(),
             default_headers: HeaderMap::new(),
             retry_strategy: RetryStrategy::None,
             retry_predicate: None,
             timeout: None,
             rate_limit_config: RateLimitConfig::default(),
+            hedge_config: None,
+            metrics_recorder: None,
+            redaction: RedactionPolicy::default(),
+            log_config: LogConfig::default(),
+            compression: CompressionConfig::default(),
+            signer: None,
+            cookie_jar: None,
+            redirect_policy: None,
+            transport: None,
+            connection_config: None,
+            tls_config: None,
+            dns_overrides: HashMap::new(),
+            dns_resolver: None,
+            reqwest_builder: None,
+            #[cfg(unix)]
+            unix_socket: None,
+            #[cfg(feature = "opentelemetry")]
+            trace_propagation: crate::telemetry::TracePropagation::default(),
         }
     }
 
@@ -519,10 +1546,65 @@ impl ClientBuilder {
     ///
     /// Returns an error if the URL is invalid.
     pub fn base_url(mut self, url: impl AsRef<str>) -> Result<Self> {
-        self.base_url = Some(Url::parse(url.as_ref())?);
+        self.base_urls = vec![Url::parse(url.as_ref())?];
+        Ok(self)
+    }
+
+    /// Sets several base URLs for a replicated service, replacing any set before.
+    ///
+    /// Each attempt is sent to one of them, chosen according to the
+    /// [`BalanceConfig`]. The first URL is used to identify the service in
+    /// traces and for host-scoped rate limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any URL is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::balance::{BalanceConfig, BalanceStrategy};
+    /// use calleen::{Client, RetryStrategy};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_urls(["http://10.0.0.1:8080", "http://10.0.0.2:8080"])?
+    ///     .balance_config(
+    ///         BalanceConfig::builder()
+    ///             .strategy(BalanceStrategy::LeastOutstanding)
+    ///             .build(),
+    ///     )
+    ///     // Retries go to a different endpoint than the one that failed
+    ///     .retry_strategy(RetryStrategy::ExponentialBackoff {
+    ///         initial_delay: std::time::Duration::from_millis(100),
+    ///         max_delay: std::time::Duration::from_secs(2),
+    ///         max_retries: 2,
+    ///         jitter: true,
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn base_urls<I>(mut self, urls: I) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.base_urls = urls
+            .into_iter()
+            .map(|url| Url::parse(url.as_ref()))
+            .collect::<std::result::Result<_, _>>()?;
         Ok(self)
     }
 
+    /// Sets how requests are balanced across the base URLs.
+    ///
+    /// This only has an effect with more than one base URL.
+    pub fn balance_config(mut self, config: BalanceConfig) -> Self {
+        self.balance_config = config;
+        self
+    }
+
     /// Adds a default header that will be included in all requests.
     ///
     /// # Errors
@@ -559,7 +1641,8 @@ impl ClientBuilder {
 
     /// Sets the rate limit configuration.
     ///
-    /// By default, rate limit handling is enabled with sensible defaults.
+    /// By default, rate limit handling is enabled with sensible defaults, and a
+    /// rate limited response pauses all requests to the same host.
     ///
     /// # Examples
     ///
@@ -582,6 +1665,446 @@ impl ClientBuilder {
         self
     }
 
+    /// Enables hedged requests for idempotent methods.
+    ///
+    /// If a request hasn't received a response within the configured delay,
+    /// a duplicate request is sent and whichever answers first is used.
+    /// Hedging is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, hedge::{HedgeConfig, HedgeDelay}};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .hedge_config(HedgeConfig::builder()
+    ///         .delay(HedgeDelay::Percentile {
+    ///             percentile: 99.0,
+    ///             fallback: std::time::Duration::from_millis(200),
+    ///         })
+    ///         .max_hedges(2)
+    ///         .build())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hedge_config(mut self, config: HedgeConfig) -> Self {
+        self.hedge_config = Some(config);
+        self
+    }
+
+    /// Sets the recorder for request metrics.
+    ///
+    /// No metrics are recorded by default. With the `metrics` feature,
+    /// [`FacadeRecorder`](crate::metrics::FacadeRecorder) records to the `metrics` facade.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, metrics::{MetricLabels, MetricsRecorder}};
+    /// use std::time::Duration;
+    ///
+    /// struct RetryLogger;
+    ///
+    /// impl MetricsRecorder for RetryLogger {
+    ///     fn record_retry(&self, labels: &MetricLabels<'_>, attempt: usize) {
+    ///         println!("Retrying {} {} after attempt {}", labels.method, labels.endpoint, attempt);
+    ///     }
+    /// }
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .metrics_recorder(Box::new(RetryLogger))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn metrics_recorder(mut self, recorder: Box<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(recorder);
+        self
+    }
+
+    /// Sets the policy for redacting sensitive data from logs and errors.
+    ///
+    /// By default, the `Authorization`, `Cookie` and `Set-Cookie` headers are
+    /// redacted. The policy is applied to every body and header calleen logs, and
+    /// to the bodies and headers stored in [`Error::HttpError`] and
+    /// [`Error::DeserializationFailed`]. Bodies in both are truncated to
+    /// `max_body_len` too.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, redact::RedactionPolicy};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .redaction_policy(RedactionPolicy::builder()
+    ///         .redact_header("x-api-key")
+    ///         .mask_field("$..access_token")
+    ///         .max_body_len(2048)
+    ///         .build())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn redaction_policy(mut self, policy: RedactionPolicy) -> Self {
+        self.redaction = policy;
+        self
+    }
+
+    /// Sets what is logged, and at which level.
+    ///
+    /// By default, every response is logged at `info` level, 4xx responses and
+    /// deserialization failures at `error` level, and 5xx responses and failed
+    /// attempts at `warn` level. Request headers and bodies aren't logged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, logging::{LogConfig, LogLevel}};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .log_config(LogConfig::builder()
+    ///         .status_level(404, LogLevel::Debug)
+    ///         .log_request_headers(true)
+    ///         .build())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn log_config(mut self, config: LogConfig) -> Self {
+        self.log_config = config;
+        self
+    }
+
+    /// Sets how request and response bodies are compressed.
+    ///
+    /// By default, responses are decompressed in every encoding whose cargo
+    /// feature is enabled (`gzip`, `deflate`, `brotli` and `zstd`), and request
+    /// bodies aren't compressed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, compression::{CompressionConfig, Encoding}};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// // Requires the `gzip` feature
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .compression_config(CompressionConfig::builder()
+    ///         .compress_requests(Encoding::Gzip)
+    ///         .build())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compression_config(mut self, config: CompressionConfig) -> Self {
+        self.compression = config;
+        self
+    }
+
+    /// Sets a signer that signs every attempt just before it's sent.
+    ///
+    /// Retries and hedged requests are signed again, so signatures that
+    /// include a timestamp stay fresh. Redirects to another origin aren't signed
+    /// unless the [`RedirectPolicy`] follows them with credentials. See
+    /// [`signing`](crate::signing).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, signing::HmacSigner};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .request_signer(Box::new(
+    ///         HmacSigner::new(b"secret".to_vec()).timestamp_header("X-Timestamp"),
+    ///     ))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_signer(mut self, signer: Box<dyn RequestSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Sets a cookie jar that stores cookies from responses and sends them
+    /// with matching requests.
+    ///
+    /// Pass a clone of the same jar to several clients to share their cookies.
+    /// See [`cookie`](crate::cookie) for an example.
+    pub fn cookie_jar(mut self, jar: CookieJar) -> Self {
+        self.cookie_jar = Some(jar);
+        self
+    }
+
+    /// Sets which redirects are followed.
+    ///
+    /// Redirects are followed by the client rather than the transport, so the
+    /// policy applies to custom transports too. By default, up to 10 redirects
+    /// are followed and credentials are dropped on redirects to another origin.
+    /// See [`redirect`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    /// use calleen::redirect::{CrossOriginRedirects, RedirectPolicy};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .redirect_policy(RedirectPolicy::builder()
+    ///         .max_redirects(3)
+    ///         .cross_origin(CrossOriginRedirects::Refuse)
+    ///         .build())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = Some(policy);
+        self
+    }
+
+    /// Sets connection pooling, TCP, HTTP/2, proxy, redirect and `User-Agent`
+    /// options for the default transport.
+    ///
+    /// This can't be combined with [`transport`](ClientBuilder::transport).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, connection::ConnectionConfig};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .connection_config(ConnectionConfig::builder()
+    ///         .pool_max_idle_per_host(16)
+    ///         .tcp_keepalive(Duration::from_secs(30))
+    ///         .user_agent("my-app/1.0")
+    ///         .build())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn connection_config(mut self, config: ConnectionConfig) -> Self {
+        self.connection_config = Some(config);
+        self
+    }
+
+    /// Sets root certificates, client certificates, the minimum TLS version and
+    /// key pinning for the default transport.
+    ///
+    /// This replaces any TLS settings of
+    /// [`reqwest_builder`](ClientBuilder::reqwest_builder), and can't be combined
+    /// with [`transport`](ClientBuilder::transport).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, tls::{Certificate, Identity, TlsConfig}};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::builder()
+    ///     .base_url("https://payments.internal")?
+    ///     .tls_config(TlsConfig::builder()
+    ///         .root_certificate(Certificate::Pem(std::fs::read("internal-ca.pem")?))
+    ///         .identity(Identity::from_pem(
+    ///             std::fs::read("client.pem")?,
+    ///             std::fs::read("client.key")?,
+    ///         ))
+    ///         .build())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tls_config(mut self, config: TlsConfig) -> Self {
+        self.tls_config = Some(config);
+        self
+    }
+
+    /// Resolves `host` to a fixed address instead of looking it up.
+    ///
+    /// A port in the request URL takes precedence over the address's port, and
+    /// a port of `0` is replaced by the scheme's default port. Calling this
+    /// again for the same host adds another address to try.
+    ///
+    /// This can't be combined with [`transport`](ClientBuilder::transport).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// // Send requests for api.example.com to a local server
+    /// let client = Client::builder()
+    ///     .base_url("http://api.example.com:8080")?
+    ///     .resolve("api.example.com", "127.0.0.1:0".parse().unwrap())
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve(mut self, host: impl AsRef<str>, addr: SocketAddr) -> Self {
+        self.dns_overrides
+            .entry(host.as_ref().to_ascii_lowercase())
+            .or_default()
+            .push(addr);
+        self
+    }
+
+    /// Sets the resolver used for hosts without a fixed address from
+    /// [`resolve`](ClientBuilder::resolve).
+    ///
+    /// See [`dns`](crate::dns) for an example. This can't be combined with
+    /// [`transport`](ClientBuilder::transport).
+    pub fn dns_resolver(mut self, resolver: Box<dyn Resolve>) -> Self {
+        self.dns_resolver = Some(resolver);
+        self
+    }
+
+    /// Builds the default transport from a preconfigured `reqwest::ClientBuilder`.
+    ///
+    /// Use this for reqwest options calleen doesn't expose. Options from
+    /// [`connection_config`](ClientBuilder::connection_config) and
+    /// [`unix_socket`](ClientBuilder::unix_socket) are applied on top of it. The
+    /// builder's own DNS resolver is kept unless [`resolve`](ClientBuilder::resolve)
+    /// or [`dns_resolver`](ClientBuilder::dns_resolver) is used.
+    /// Its redirect policy is replaced, since the client follows redirects itself
+    /// according to [`redirect_policy`](ClientBuilder::redirect_policy).
+    /// This can't be combined with [`transport`](ClientBuilder::transport).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .reqwest_builder(reqwest::Client::builder().https_only(true))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reqwest_builder(mut self, builder: reqwest::ClientBuilder) -> Self {
+        self.reqwest_builder = Some(builder);
+        self
+    }
+
+    /// Connects to a Unix domain socket instead of the base URL's host.
+    ///
+    /// The base URL is still used for the request path and the `Host` header, and
+    /// its scheme decides whether TLS is used over the socket. This is useful for
+    /// local daemons, such as Docker, that serve HTTP over a socket.
+    ///
+    /// This can't be combined with [`transport`](ClientBuilder::transport).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let docker = Client::builder()
+    ///     .base_url("http://localhost")?
+    ///     .unix_socket("/var/run/docker.sock")
+    ///     .build()?;
+    ///
+    /// let version = docker.get::<serde_json::Value>("/version").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn unix_socket(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.unix_socket = Some(path.into());
+        self
+    }
+
+    /// Sets the transport that sends requests.
+    ///
+    /// By default, requests are sent with a
+    /// [`ReqwestTransport`]. Retries, hedging,
+    /// rate limiting and timeouts are applied around any transport.
+    pub fn transport(mut self, transport: Box<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Sends requests through a [`MockTransport`](crate::mock::MockTransport)
+    /// instead of the network.
+    ///
+    /// Retries, hedging, rate limiting and timeouts still apply, so they can be
+    /// tested against scripted responses and failures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calleen::{Client, mock::{Mock, MockResponse, MockTransport}};
+    /// use http::Method;
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let transport = MockTransport::new();
+    /// transport.register(
+    ///     Mock::new(Method::GET, "/users/1")
+    ///         .respond_with(MockResponse::json(200, &serde_json::json!({"id": 1}))),
+    /// );
+    ///
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .mock_transport(transport.clone())
+    ///     .build()?;
+    ///
+    /// let user = client.get::<serde_json::Value>("/users/1").await?;
+    /// assert_eq!(user.data["id"], 1);
+    /// transport.verify();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "test-util")]
+    pub fn mock_transport(self, transport: crate::mock::MockTransport) -> Self {
+        self.transport(Box::new(transport))
+    }
+
+    /// Sets which trace context headers are injected into outgoing requests.
+    ///
+    /// By default, W3C `traceparent` and `tracestate` headers are injected.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::{Client, telemetry::{B3Format, TracePropagation}};
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .trace_propagation(TracePropagation {
+    ///         w3c: true,
+    ///         b3: Some(B3Format::Multi),
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "opentelemetry")]
+    pub fn trace_propagation(mut self, propagation: crate::telemetry::TracePropagation) -> Self {
+        self.trace_propagation = propagation;
+        self
+    }
+
     /// Builds the configured `Client`.
     ///
     /// # Errors
@@ -589,13 +2112,85 @@ impl ClientBuilder {
     /// Returns an error if no base URL was provided or if the client
     /// configuration is invalid.
     pub fn build(self) -> Result<Client> {
-        let base_url = self
-            .base_url
-            .ok_or_else(|| Error::ConfigurationError("Base URL is required".to_string()))?;
+        if self.base_urls.is_empty() {
+            return Err(Error::ConfigurationError(
+                "Base URL is required".to_string(),
+            ));
+        }
+        self.compression.validate()?;
+
+        let max_redirects = self
+            .connection_config
+            .as_ref()
+            .and_then(|config| config.max_redirects);
+        let redirect_policy = match (self.redirect_policy, max_redirects) {
+            (Some(_), Some(_)) => {
+                return Err(Error::ConfigurationError(
+                    "max_redirects can't be used with a redirect policy".to_string(),
+                ));
+            }
+            (Some(policy), None) => policy,
+            (None, Some(max_redirects)) => RedirectPolicy {
+                max_redirects,
+                ..RedirectPolicy::default()
+            },
+            (None, None) => RedirectPolicy::default(),
+        };
 
-        let http_client = reqwest::Client::builder().build().map_err(|e| {
-            Error::ConfigurationError(format!("Failed to build HTTP client: {}", e))
-        })?;
+        let custom_dns = !self.dns_overrides.is_empty() || self.dns_resolver.is_some();
+        if self.transport.is_some()
+            && (self.connection_config.is_some()
+                || self.tls_config.is_some()
+                || custom_dns
+                || self.reqwest_builder.is_some())
+        {
+            return Err(Error::ConfigurationError(
+                "Connection options can't be used with a custom transport".to_string(),
+            ));
+        }
+        #[cfg(unix)]
+        if self.transport.is_some() && self.unix_socket.is_some() {
+            return Err(Error::ConfigurationError(
+                "A Unix socket can't be used with a custom transport".to_string(),
+            ));
+        }
+
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => {
+                // Keep reqwest's resolver, or that of a preconfigured builder,
+                // unless DNS is configured
+                let builder = self.reqwest_builder.unwrap_or_default();
+                let builder = if custom_dns {
+                    builder.dns_resolver(Arc::new(ReqwestResolver::new(
+                        self.dns_overrides,
+                        self.dns_resolver,
+                    )))
+                } else {
+                    builder
+                };
+                let builder = match &self.connection_config {
+                    Some(config) => config.apply(builder)?,
+                    None => builder,
+                };
+                let builder = match &self.tls_config {
+                    Some(config) => builder.use_preconfigured_tls(config.client_config()?),
+                    None => builder,
+                };
+                #[cfg(unix)]
+                let builder = match self.unix_socket {
+                    Some(path) => builder.unix_socket(path),
+                    None => builder,
+                };
+                // Redirects are followed by the client, according to its policy
+                let builder = builder.redirect(reqwest::redirect::Policy::none());
+
+                let http_client = builder.build().map_err(|e| {
+                    Error::ConfigurationError(format!("Failed to build HTTP client: {}", e))
+                })?;
+                Box::new(ReqwestTransport::new(http_client))
+            }
+        };
 
         let retry_predicate = self
             .retry_predicate
@@ -603,13 +2198,25 @@ impl ClientBuilder {
 
         Ok(Client {
             inner: Arc::new(ClientInner {
-                http_client,
-                base_url,
+                transport,
+                endpoints: Endpoints::new(self.base_urls, self.balance_config),
                 default_headers: self.default_headers,
                 retry_strategy: self.retry_strategy,
                 retry_predicate,
                 timeout: self.timeout,
                 rate_limit_config: self.rate_limit_config,
+                hedge_config: self.hedge_config,
+                rate_limit_gates: RateLimitGates::default(),
+                latencies: LatencyTracker::default(),
+                metrics_recorder: self.metrics_recorder,
+                redaction: self.redaction,
+                log_config: self.log_config,
+                compression: self.compression,
+                signer: self.signer,
+                cookie_jar: self.cookie_jar,
+                redirect_policy,
+                #[cfg(feature = "opentelemetry")]
+                trace_propagation: self.trace_propagation,
             }),
         })
     }
@@ -0,0 +1,406 @@
+//! Compression of request and response bodies.
+//!
+//! Each encoding is enabled by the cargo feature of the same name: `gzip`,
+//! `deflate`, `brotli` and `zstd`. With any of them enabled, the client
+//! advertises the enabled encodings in `Accept-Encoding` and decompresses
+//! responses before they're decoded, so [`Response::raw_body`](crate::Response::raw_body)
+//! and the bodies stored in errors always hold the decompressed text.
+//!
+//! Request bodies can also be compressed, see [`CompressionConfig`].
+//! Compression is applied by the client around the transport, so it works the
+//! same with any [`Transport`](crate::transport::Transport).
+
+use crate::error::{NetworkError, NetworkErrorKind};
+use crate::{Error, Result};
+use http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH};
+use http::{HeaderMap, HeaderValue};
+use std::io;
+
+/// A content encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// `gzip`. Requires the `gzip` feature.
+    Gzip,
+    /// `deflate`, the zlib format. Requires the `deflate` feature.
+    Deflate,
+    /// `br`. Requires the `brotli` feature.
+    Brotli,
+    /// `zstd`. Requires the `zstd` feature.
+    Zstd,
+}
+
+impl Encoding {
+    const ALL: [Encoding; 4] = [
+        Encoding::Zstd,
+        Encoding::Brotli,
+        Encoding::Gzip,
+        Encoding::Deflate,
+    ];
+
+    /// Returns the name of the encoding in `Content-Encoding` headers.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    /// Returns `true` if the cargo feature for this encoding is enabled.
+    pub fn is_enabled(self) -> bool {
+        match self {
+            Encoding::Gzip => cfg!(feature = "gzip"),
+            Encoding::Deflate => cfg!(feature = "deflate"),
+            Encoding::Brotli => cfg!(feature = "brotli"),
+            Encoding::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    fn feature(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Brotli => "brotli",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|encoding| name.eq_ignore_ascii_case(encoding.as_str()))
+            // `x-gzip` is an alias for `gzip` (RFC 9110 section 8.4.1.3)
+            .or_else(|| {
+                name.eq_ignore_ascii_case("x-gzip")
+                    .then_some(Encoding::Gzip)
+            })
+    }
+
+    #[allow(unused_variables, unreachable_patterns)]
+    fn encode(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                io::Write::write_all(&mut encoder, data)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => {
+                let mut encoder =
+                    flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                io::Write::write_all(&mut encoder, data)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+                io::Write::write_all(&mut encoder, data)?;
+                Ok(encoder.into_inner())
+            }
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => zstd::encode_all(data, 3),
+            _ => Err(self.disabled()),
+        }
+    }
+
+    /// Decodes `data`, failing if it decodes to more than `limit` bytes.
+    #[allow(unused_variables, unreachable_patterns)]
+    fn decode(self, data: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => read_all(flate2::read::MultiGzDecoder::new(data), limit),
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => read_all(flate2::read::ZlibDecoder::new(data), limit),
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => read_all(brotli::Decompressor::new(data, 4096), limit),
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => read_all(zstd::stream::read::Decoder::new(data)?, limit),
+            _ => Err(self.disabled()),
+        }
+    }
+
+    fn disabled(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("the `{}` feature isn't enabled", self.feature()),
+        )
+    }
+}
+
+/// Reads everything from `reader`, failing if there's more than `limit` bytes.
+#[cfg(any(
+    feature = "gzip",
+    feature = "deflate",
+    feature = "brotli",
+    feature = "zstd"
+))]
+fn read_all(reader: impl io::Read, limit: usize) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    // Read one byte past the limit to tell whether it was exceeded
+    let mut reader = io::Read::take(reader, (limit as u64).saturating_add(1));
+    io::Read::read_to_end(&mut reader, &mut data)?;
+    if data.len() > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decompressed body is larger than {} bytes", limit),
+        ));
+    }
+    Ok(data)
+}
+
+/// Configuration for compressing request and response bodies.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::compression::{CompressionConfig, Encoding};
+///
+/// // Gzip request bodies of 4 KiB or more
+/// let config = CompressionConfig::builder()
+///     .compress_requests(Encoding::Gzip)
+///     .min_request_size(4096)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    /// Whether to advertise the enabled encodings in `Accept-Encoding` and
+    /// decompress responses. Defaults to `true`.
+    ///
+    /// If a request sets its own `Accept-Encoding` header, it's sent unchanged,
+    /// and responses in an enabled encoding are still decompressed.
+    pub decompress_responses: bool,
+
+    /// The encoding to compress request bodies with. Defaults to `None`, which
+    /// sends request bodies uncompressed.
+    pub request_encoding: Option<Encoding>,
+
+    /// The minimum size of a request body, in bytes, for it to be compressed.
+    /// Defaults to 1024.
+    pub min_request_size: usize,
+
+    /// The maximum size of a decompressed response body, in bytes. Larger
+    /// responses fail with a [`NetworkErrorKind::Body`] error, so a small
+    /// compressed response can't exhaust memory. Defaults to 64 MiB.
+    pub max_decompressed_size: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            decompress_responses: true,
+            request_encoding: None,
+            min_request_size: 1024,
+            max_decompressed_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Creates a new builder for configuring compression.
+    pub fn builder() -> CompressionConfigBuilder {
+        CompressionConfigBuilder {
+            config: Self::default(),
+        }
+    }
+
+    /// Checks that the request encoding's feature is enabled.
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self.request_encoding {
+            Some(encoding) if !encoding.is_enabled() => Err(Error::ConfigurationError(format!(
+                "Request compression with {} requires the `{}` feature",
+                encoding.as_str(),
+                encoding.feature()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds an `Accept-Encoding` header listing the enabled encodings, unless
+    /// the request already has one.
+    pub(crate) fn add_accept_encoding(&self, headers: &mut HeaderMap) {
+        if !self.decompress_responses || headers.contains_key(ACCEPT_ENCODING) {
+            return;
+        }
+
+        let accepted = Encoding::ALL
+            .into_iter()
+            .filter(|encoding| encoding.is_enabled())
+            .map(Encoding::as_str)
+            .collect::<Vec<_>>();
+        if accepted.is_empty() {
+            return;
+        }
+        if let Ok(value) = HeaderValue::from_str(&accepted.join(", ")) {
+            headers.insert(ACCEPT_ENCODING, value);
+        }
+    }
+
+    /// Compresses a request body if it's large enough, setting `Content-Encoding`.
+    pub(crate) fn compress_request(
+        &self,
+        headers: &mut HeaderMap,
+        body: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let Some(encoding) = self.request_encoding else {
+            return Ok(body);
+        };
+        if body.len() < self.min_request_size || headers.contains_key(CONTENT_ENCODING) {
+            return Ok(body);
+        }
+
+        let compressed = encoding.encode(&body).map_err(|e| {
+            Error::SerializationFailed(format!(
+                "Failed to compress request body with {}: {}",
+                encoding.as_str(),
+                e
+            ))
+        })?;
+        headers.insert(
+            CONTENT_ENCODING,
+            HeaderValue::from_static(encoding.as_str()),
+        );
+        Ok(compressed)
+    }
+
+    /// Decompresses a response body in the encodings listed in its
+    /// `Content-Encoding` header.
+    ///
+    /// Responses in an encoding that isn't enabled are returned unchanged.
+    pub(crate) fn decompress_response(
+        &self,
+        response: http::Response<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>> {
+        // Empty bodies, e.g. of HEAD responses, aren't encoded
+        if !self.decompress_responses || response.body().is_empty() {
+            return Ok(response);
+        }
+
+        let (mut parts, mut body) = response.into_parts();
+        let Some(encodings) = parts
+            .headers
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty() && !name.eq_ignore_ascii_case("identity"))
+                    .map(Encoding::from_name)
+                    .collect::<Option<Vec<_>>>()
+            })
+        else {
+            return Ok(http::Response::from_parts(parts, body));
+        };
+
+        // Leave responses we can't fully decode untouched
+        let Some(encodings) = encodings.filter(|e| e.iter().all(|e| e.is_enabled())) else {
+            return Ok(http::Response::from_parts(parts, body));
+        };
+
+        // Encodings are listed in the order they were applied
+        for encoding in encodings.into_iter().rev() {
+            body = encoding
+                .decode(&body, self.max_decompressed_size)
+                .map_err(|e| {
+                    NetworkError::new(
+                        NetworkErrorKind::Body,
+                        format!(
+                            "Failed to decompress {} response body: {}",
+                            encoding.as_str(),
+                            e
+                        ),
+                    )
+                    .with_source(e)
+                })?;
+        }
+        parts.headers.remove(CONTENT_ENCODING);
+        parts.headers.remove(CONTENT_LENGTH);
+        Ok(http::Response::from_parts(parts, body))
+    }
+}
+
+/// Builder for `CompressionConfig`.
+pub struct CompressionConfigBuilder {
+    config: CompressionConfig,
+}
+
+impl CompressionConfigBuilder {
+    /// Sets whether to advertise the enabled encodings and decompress responses.
+    pub fn decompress_responses(mut self, enabled: bool) -> Self {
+        self.config.decompress_responses = enabled;
+        self
+    }
+
+    /// Compresses request bodies with the given encoding.
+    pub fn compress_requests(mut self, encoding: Encoding) -> Self {
+        self.config.request_encoding = Some(encoding);
+        self
+    }
+
+    /// Sets the minimum size of a request body, in bytes, for it to be compressed.
+    pub fn min_request_size(mut self, size: usize) -> Self {
+        self.config.min_request_size = size;
+        self
+    }
+
+    /// Sets the maximum size of a decompressed response body, in bytes.
+    pub fn max_decompressed_size(mut self, size: usize) -> Self {
+        self.config.max_decompressed_size = size;
+        self
+    }
+
+    /// Builds the `CompressionConfig`.
+    pub fn build(self) -> CompressionConfig {
+        self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_names() {
+        assert_eq!(Encoding::from_name("GZIP"), Some(Encoding::Gzip));
+        assert_eq!(Encoding::from_name("x-gzip"), Some(Encoding::Gzip));
+        assert_eq!(Encoding::from_name("br"), Some(Encoding::Brotli));
+        assert_eq!(Encoding::from_name("compress"), None);
+    }
+
+    #[test]
+    fn test_unknown_encoding_is_left_alone() {
+        let response = http::Response::builder()
+            .header(CONTENT_ENCODING, "compress")
+            .body(b"data".to_vec())
+            .unwrap();
+
+        let response = CompressionConfig::default()
+            .decompress_response(response)
+            .unwrap();
+        assert_eq!(response.headers()[CONTENT_ENCODING], "compress");
+        assert_eq!(response.body(), b"data");
+    }
+
+    #[cfg(all(feature = "gzip", feature = "brotli"))]
+    #[test]
+    fn test_stacked_encodings() {
+        let body = Encoding::Brotli
+            .encode(&Encoding::Gzip.encode(b"{\"id\": 1}").unwrap())
+            .unwrap();
+        let response = http::Response::builder()
+            .header(CONTENT_ENCODING, "gzip, br")
+            .header(CONTENT_LENGTH, body.len())
+            .body(body)
+            .unwrap();
+
+        let response = CompressionConfig::default()
+            .decompress_response(response)
+            .unwrap();
+        assert_eq!(response.body(), b"{\"id\": 1}");
+        assert!(response.headers().get(CONTENT_ENCODING).is_none());
+        assert!(response.headers().get(CONTENT_LENGTH).is_none());
+    }
+}
@@ -0,0 +1,283 @@
+//! Tuning for the connections of the default transport.
+//!
+//! [`ConnectionConfig`] sets connection pooling, TCP options, HTTP/2, proxies,
+//! redirects, the local address and the `User-Agent` of the
+//! [`ReqwestTransport`](crate::transport::ReqwestTransport) that
+//! [`ClientBuilder::build`](crate::ClientBuilder::build) creates. Options that
+//! aren't set keep reqwest's defaults.
+//!
+//! For options not covered here, pass a preconfigured `reqwest::ClientBuilder`
+//! to [`ClientBuilder::reqwest_builder`](crate::ClientBuilder::reqwest_builder).
+
+use crate::{Error, Result};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Connection settings for the default transport.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::connection::{ConnectionConfig, Proxy};
+/// use std::time::Duration;
+///
+/// let config = ConnectionConfig::builder()
+///     .pool_idle_timeout(Duration::from_secs(30))
+///     .pool_max_idle_per_host(8)
+///     .tcp_keepalive(Duration::from_secs(60))
+///     .proxy(Proxy::https("http://proxy.internal:3128").no_proxy("localhost,.internal"))
+///     .max_redirects(3)
+///     .user_agent("my-app/1.0")
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionConfig {
+    /// How long an idle pooled connection is kept open.
+    pub pool_idle_timeout: Option<Duration>,
+
+    /// The maximum number of idle connections kept per host.
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// The interval of TCP keepalive probes. `None` leaves keepalive disabled.
+    pub tcp_keepalive: Option<Duration>,
+
+    /// Whether to set `TCP_NODELAY` on connections. reqwest enables it by default.
+    pub tcp_nodelay: Option<bool>,
+
+    /// Whether to speak HTTP/2 without negotiating it first.
+    ///
+    /// Only use this for servers known to support HTTP/2, as HTTP/1 servers will
+    /// reject every request. Defaults to `false`.
+    pub http2_prior_knowledge: bool,
+
+    /// Whether to use an adaptive flow control window for HTTP/2 connections.
+    pub http2_adaptive_window: Option<bool>,
+
+    /// Proxies to send requests through, in order of precedence.
+    pub proxies: Vec<Proxy>,
+
+    /// Whether to ignore proxies set in the environment, such as `HTTPS_PROXY`.
+    ///
+    /// Proxies in [`proxies`](ConnectionConfig::proxies) are still used.
+    /// Defaults to `false`.
+    pub no_system_proxy: bool,
+
+    /// The maximum number of redirects followed per request. `Some(0)` disables
+    /// redirects. Up to 10 are followed by default.
+    ///
+    /// This is shorthand for [`RedirectPolicy::max_redirects`](crate::redirect::RedirectPolicy::max_redirects)
+    /// and can't be combined with [`ClientBuilder::redirect_policy`](crate::ClientBuilder::redirect_policy).
+    pub max_redirects: Option<usize>,
+
+    /// The local IP address to connect from.
+    pub local_address: Option<IpAddr>,
+
+    /// The `User-Agent` header sent with every request.
+    pub user_agent: Option<String>,
+}
+
+impl ConnectionConfig {
+    /// Creates a new builder for configuring connections.
+    pub fn builder() -> ConnectionConfigBuilder {
+        ConnectionConfigBuilder {
+            config: Self::default(),
+        }
+    }
+
+    /// Applies the settings to a reqwest client builder.
+    pub(crate) fn apply(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder> {
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(enabled) = self.tcp_nodelay {
+            builder = builder.tcp_nodelay(enabled);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(enabled) = self.http2_adaptive_window {
+            builder = builder.http2_adaptive_window(enabled);
+        }
+        if self.no_system_proxy {
+            builder = builder.no_proxy();
+        }
+        for proxy in &self.proxies {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        if let Some(address) = self.local_address {
+            builder = builder.local_address(address);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder)
+    }
+}
+
+/// Builder for `ConnectionConfig`.
+pub struct ConnectionConfigBuilder {
+    config: ConnectionConfig,
+}
+
+impl ConnectionConfigBuilder {
+    /// Sets how long an idle pooled connection is kept open.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept per host.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.config.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Enables TCP keepalive probes at the given interval.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.config.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Sets whether to set `TCP_NODELAY` on connections.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.config.tcp_nodelay = Some(enabled);
+        self
+    }
+
+    /// Sets whether to speak HTTP/2 without negotiating it first.
+    pub fn http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.config.http2_prior_knowledge = enabled;
+        self
+    }
+
+    /// Sets whether to use an adaptive flow control window for HTTP/2 connections.
+    pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+        self.config.http2_adaptive_window = Some(enabled);
+        self
+    }
+
+    /// Adds a proxy to send requests through.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxies.push(proxy);
+        self
+    }
+
+    /// Sets whether to ignore proxies set in the environment.
+    pub fn no_system_proxy(mut self, enabled: bool) -> Self {
+        self.config.no_system_proxy = enabled;
+        self
+    }
+
+    /// Sets the maximum number of redirects followed per request.
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.config.max_redirects = Some(max);
+        self
+    }
+
+    /// Sets the local IP address to connect from.
+    pub fn local_address(mut self, address: IpAddr) -> Self {
+        self.config.local_address = Some(address);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Builds the `ConnectionConfig`.
+    pub fn build(self) -> ConnectionConfig {
+        self.config
+    }
+}
+
+/// Which requests a [`Proxy`] is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyScope {
+    /// Every request.
+    All,
+    /// Requests to `http` URLs.
+    Http,
+    /// Requests to `https` URLs.
+    Https,
+}
+
+/// A proxy to send requests through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proxy {
+    /// Which requests the proxy is used for.
+    pub scope: ProxyScope,
+    /// The proxy URL, e.g. `http://proxy.internal:3128`.
+    pub url: String,
+    /// The username and password to authenticate with the proxy.
+    pub basic_auth: Option<(String, String)>,
+    /// A comma-separated list of hosts, domains and IP ranges that bypass the proxy,
+    /// in the same format as the `NO_PROXY` environment variable.
+    pub no_proxy: Option<String>,
+}
+
+impl Proxy {
+    /// Creates a proxy for every request.
+    pub fn all(url: impl Into<String>) -> Self {
+        Self::new(ProxyScope::All, url)
+    }
+
+    /// Creates a proxy for requests to `http` URLs.
+    pub fn http(url: impl Into<String>) -> Self {
+        Self::new(ProxyScope::Http, url)
+    }
+
+    /// Creates a proxy for requests to `https` URLs.
+    pub fn https(url: impl Into<String>) -> Self {
+        Self::new(ProxyScope::Https, url)
+    }
+
+    fn new(scope: ProxyScope, url: impl Into<String>) -> Self {
+        Self {
+            scope,
+            url: url.into(),
+            basic_auth: None,
+            no_proxy: None,
+        }
+    }
+
+    /// Authenticates with the proxy using HTTP basic authentication.
+    pub fn basic_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.basic_auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Sets the hosts that bypass the proxy, e.g. `localhost,.internal,10.0.0.0/8`.
+    pub fn no_proxy(mut self, hosts: impl Into<String>) -> Self {
+        self.no_proxy = Some(hosts.into());
+        self
+    }
+
+    fn to_reqwest(&self) -> Result<reqwest::Proxy> {
+        let proxy = match self.scope {
+            ProxyScope::All => reqwest::Proxy::all(&self.url),
+            ProxyScope::Http => reqwest::Proxy::http(&self.url),
+            ProxyScope::Https => reqwest::Proxy::https(&self.url),
+        }
+        .map_err(|e| Error::ConfigurationError(format!("Invalid proxy {}: {}", self.url, e)))?;
+
+        let proxy = match &self.basic_auth {
+            Some((username, password)) => proxy.basic_auth(username, password),
+            None => proxy,
+        };
+        Ok(match &self.no_proxy {
+            Some(hosts) => proxy.no_proxy(reqwest::NoProxy::from_string(hosts)),
+            None => proxy,
+        })
+    }
+}
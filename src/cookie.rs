@@ -0,0 +1,590 @@
+//! Cookie storage for services that use session cookies.
+//!
+//! A [`CookieJar`] set with [`ClientBuilder::cookie_jar`](crate::ClientBuilder::cookie_jar)
+//! stores the cookies from every `Set-Cookie` response header and sends them
+//! back in the `Cookie` header of later requests whose domain and path match,
+//! following [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265). The jar is
+//! shared by clones of the client, and by any clients given a clone of it.
+//!
+//! Cookies for a top-level domain such as `com` are rejected. Other public
+//! suffixes, such as `co.uk`, aren't known to the jar, so only use it with
+//! services you trust not to set cookies for them.
+//!
+//! A jar created with [`CookieJar::persistent`] is loaded from a JSON file and
+//! saved back to it whenever its cookies change, so sessions survive restarts.
+//! Session cookies, which have no expiry, are persisted too. Cookies received in
+//! responses are saved on tokio's blocking thread pool, so writing the file
+//! doesn't hold up requests.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use calleen::{Client, cookie::CookieJar};
+//!
+//! # async fn example() -> Result<(), calleen::Error> {
+//! let client = Client::builder()
+//!     .base_url("https://legacy.example.com")?
+//!     .cookie_jar(CookieJar::persistent("cookies.json")?)
+//!     .build()?;
+//!
+//! // The session cookie set by the login response is sent with later requests
+//! client.post::<_, ()>("/login", &serde_json::json!({"user": "alice"})).await?;
+//! let profile = client.get::<serde_json::Value>("/profile").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Error, Result};
+use http::header::{COOKIE, SET_COOKIE};
+use http::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Stores cookies from responses and adds them to matching requests.
+///
+/// Cloning a jar gives another handle to the same cookies.
+#[derive(Debug, Clone, Default)]
+pub struct CookieJar {
+    inner: Arc<Mutex<JarState>>,
+    file: Option<Arc<CookieFile>>,
+}
+
+#[derive(Debug, Default)]
+struct JarState {
+    cookies: Vec<StoredCookie>,
+    next_creation: u64,
+    /// Incremented every time the cookies change.
+    generation: u64,
+}
+
+/// The JSON file a persistent jar is saved to.
+#[derive(Debug)]
+struct CookieFile {
+    path: PathBuf,
+    /// The generation of the cookies last written to the file.
+    written: Mutex<u64>,
+}
+
+/// A cookie as it's kept in the jar and its JSON file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    domain: String,
+    /// Whether the cookie is only sent to `domain` itself, not its subdomains.
+    host_only: bool,
+    path: String,
+    /// When the cookie expires, in seconds since the Unix epoch.
+    expires: Option<u64>,
+    secure: bool,
+    http_only: bool,
+    creation: u64,
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url, host: &str) -> bool {
+        let domain_matches = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(host, &self.domain)
+        };
+        domain_matches
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+    }
+}
+
+impl CookieJar {
+    /// Creates an empty jar that is kept in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a jar that is loaded from and saved to the JSON file at `path`.
+    ///
+    /// The file doesn't need to exist yet. Failures to save it are logged and
+    /// otherwise ignored, so they don't fail requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read or parsed.
+    pub fn persistent(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let cookies: Vec<StoredCookie> = match std::fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).map_err(|e| {
+                Error::ConfigurationError(format!("Invalid cookie file {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(Error::ConfigurationError(format!(
+                    "Failed to read cookie file {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        let now = unix_now();
+        let cookies: Vec<_> = cookies
+            .into_iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .collect();
+        let next_creation = cookies.iter().map(|c| c.creation + 1).max().unwrap_or(0);
+        Ok(Self {
+            inner: Arc::new(Mutex::new(JarState {
+                cookies,
+                next_creation,
+                generation: 0,
+            })),
+            file: Some(Arc::new(CookieFile {
+                path,
+                written: Mutex::new(0),
+            })),
+        })
+    }
+
+    /// Returns the names and values of the cookies that would be sent to `url`,
+    /// in the order they would be sent.
+    pub fn cookies(&self, url: &Url) -> Vec<(String, String)> {
+        let Some(host) = url.host_str() else {
+            return Vec::new();
+        };
+        let host = host.to_ascii_lowercase();
+        let now = unix_now();
+
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut cookies: Vec<&StoredCookie> = state
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now) && cookie.matches(url, &host))
+            .collect();
+        // More specific paths first, then oldest first
+        cookies.sort_by_key(|cookie| (std::cmp::Reverse(cookie.path.len()), cookie.creation));
+        cookies
+            .into_iter()
+            .map(|cookie| (cookie.name.clone(), cookie.value.clone()))
+            .collect()
+    }
+
+    /// Stores a cookie as if `url` had responded with the given `Set-Cookie`
+    /// header value.
+    ///
+    /// Cookies that are invalid for `url`, such as ones for another domain, are
+    /// ignored. A persistent jar's file is saved before this returns.
+    pub fn set_cookie(&self, url: &Url, set_cookie: &str) {
+        let snapshot = self.store(url, std::iter::once(set_cookie));
+        self.save(snapshot, false);
+    }
+
+    /// Removes every cookie, saving a persistent jar's file before returning.
+    pub fn clear(&self) {
+        let snapshot = {
+            let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            state.cookies.clear();
+            self.snapshot(&mut state)
+        };
+        self.save(snapshot, false);
+    }
+
+    /// Adds the cookies for `url` to the request's `Cookie` header.
+    pub(crate) fn add_cookie_header(&self, url: &Url, headers: &mut HeaderMap) {
+        let cookies = self.cookies(url);
+        if cookies.is_empty() {
+            return;
+        }
+
+        // Keep any cookies set on the request itself
+        let mut pairs: Vec<String> = headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(str::to_string)
+            .collect();
+        pairs.extend(
+            cookies
+                .into_iter()
+                .map(|(name, value)| format!("{}={}", name, value)),
+        );
+        if let Ok(value) = HeaderValue::from_str(&pairs.join("; ")) {
+            headers.insert(COOKIE, value);
+        }
+    }
+
+    /// Stores the cookies from the `Set-Cookie` headers of a response from `url`.
+    pub(crate) fn store_response(&self, url: &Url, headers: &HeaderMap) {
+        let snapshot = self.store(
+            url,
+            headers
+                .get_all(SET_COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok()),
+        );
+        self.save(snapshot, true);
+    }
+
+    /// Stores cookies from `Set-Cookie` header values received from `url`,
+    /// returning a snapshot to save if the cookies changed.
+    fn store<'a>(&self, url: &Url, set_cookies: impl Iterator<Item = &'a str>) -> Option<Snapshot> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let now = unix_now();
+
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut changed = false;
+        for set_cookie in set_cookies {
+            let Some(mut cookie) = parse_set_cookie(set_cookie, url, &host, now) else {
+                tracing::debug!(url = %url, "Ignoring invalid Set-Cookie header");
+                continue;
+            };
+
+            let existing = state.cookies.iter().position(|c| {
+                c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path
+            });
+            if let Some(index) = existing {
+                cookie.creation = state.cookies.remove(index).creation;
+            } else {
+                cookie.creation = state.next_creation;
+                state.next_creation += 1;
+            }
+            // An expiry in the past deletes the cookie
+            if !cookie.is_expired(now) {
+                state.cookies.push(cookie);
+            }
+            changed = true;
+        }
+
+        if !changed {
+            return None;
+        }
+        state.cookies.retain(|cookie| !cookie.is_expired(now));
+        self.snapshot(&mut state)
+    }
+
+    /// Records that the cookies changed, returning a copy of them to save if the
+    /// jar has a file.
+    fn snapshot(&self, state: &mut JarState) -> Option<Snapshot> {
+        state.generation += 1;
+        self.file.as_ref()?;
+        Some(Snapshot {
+            generation: state.generation,
+            cookies: state.cookies.clone(),
+        })
+    }
+
+    /// Writes a snapshot to the jar's file, on the blocking thread pool if
+    /// `background` is set and a tokio runtime is running.
+    ///
+    /// Must be called without the jar locked, so writing doesn't block requests.
+    fn save(&self, snapshot: Option<Snapshot>, background: bool) {
+        let (Some(file), Some(snapshot)) = (&self.file, snapshot) else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) if background => {
+                let file = file.clone();
+                runtime.spawn_blocking(move || file.write(snapshot));
+            }
+            _ => file.write(snapshot),
+        }
+    }
+}
+
+/// The cookies of a jar at one point in time.
+struct Snapshot {
+    generation: u64,
+    cookies: Vec<StoredCookie>,
+}
+
+impl CookieFile {
+    /// Writes a snapshot to the file, unless a newer one was already written.
+    fn write(&self, snapshot: Snapshot) {
+        let mut written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+        if *written >= snapshot.generation {
+            return;
+        }
+
+        // Write to a temporary file first so a crash can't leave a partial file
+        let path = &self.path;
+        let result = serde_json::to_vec_pretty(&snapshot.cookies)
+            .map_err(std::io::Error::from)
+            .and_then(|json| {
+                let temporary = path.with_extension("tmp");
+                std::fs::write(&temporary, json)?;
+                std::fs::rename(&temporary, path)
+            });
+        match result {
+            Ok(()) => *written = snapshot.generation,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Failed to save cookies")
+            }
+        }
+    }
+}
+
+/// Parses a `Set-Cookie` header value received from `url`, returning `None` if
+/// it's malformed or not allowed for the URL's host.
+fn parse_set_cookie(set_cookie: &str, url: &Url, host: &str, now: u64) -> Option<StoredCookie> {
+    let mut parts = set_cookie.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = StoredCookie {
+        name: name.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        domain: host.to_string(),
+        host_only: true,
+        path: default_path(url.path()),
+        expires: None,
+        secure: false,
+        http_only: false,
+        creation: 0,
+    };
+    let mut max_age = None;
+
+    for attribute in parts {
+        let (key, value) = match attribute.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (attribute.trim(), ""),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "domain" if !value.is_empty() => {
+                let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                if !domain_matches(host, &domain) {
+                    return None;
+                }
+                // A top-level domain like `com` would share the cookie with every
+                // site under it, so it's only allowed as the host itself, and then
+                // only for that host (RFC 6265 section 5.3, step 5)
+                if !domain.contains('.') {
+                    if domain != host {
+                        return None;
+                    }
+                    continue;
+                }
+                cookie.domain = domain;
+                cookie.host_only = false;
+            }
+            "path" if value.starts_with('/') => cookie.path = value.to_string(),
+            "expires" => {
+                if let Ok(expires) = httpdate::parse_http_date(value) {
+                    cookie.expires = Some(unix_seconds(expires));
+                }
+            }
+            "max-age" => max_age = value.parse::<i64>().ok(),
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            _ => {}
+        }
+    }
+
+    // Max-Age takes precedence over Expires
+    if let Some(max_age) = max_age {
+        cookie.expires = Some(if max_age <= 0 {
+            0
+        } else {
+            now.saturating_add(max_age as u64)
+        });
+    }
+    Some(cookie)
+}
+
+/// Returns whether `host` is `domain` or one of its subdomains.
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host[..host.len() - domain.len()].ends_with('.')
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+/// Returns whether a request path is within a cookie's path.
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// Returns the path a cookie applies to when it doesn't set one: the request
+/// path up to its last `/`.
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+fn unix_now() -> u64 {
+    unix_seconds(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    fn names(jar: &CookieJar, target: &str) -> Vec<String> {
+        jar.cookies(&url(target))
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn test_domain_matching() {
+        let jar = CookieJar::new();
+        let origin = url("https://api.example.com/");
+        jar.set_cookie(&origin, "host=1");
+        jar.set_cookie(&origin, "domain=1; Domain=.example.com");
+        jar.set_cookie(&origin, "other=1; Domain=other.com");
+        jar.set_cookie(&origin, "tld=1; Domain=com");
+        jar.set_cookie(&origin, "dot_tld=1; Domain=.COM");
+
+        assert_eq!(names(&jar, "https://api.example.com/"), ["host", "domain"]);
+        assert_eq!(names(&jar, "https://www.example.com/"), ["domain"]);
+        assert!(names(&jar, "https://example.org/").is_empty());
+        assert!(names(&jar, "https://other.com/").is_empty());
+
+        // A single-label domain only applies to that exact host
+        let local = url("http://localhost/");
+        jar.set_cookie(&local, "local=1; Domain=localhost");
+        assert_eq!(names(&jar, "http://localhost/"), ["local"]);
+        assert!(names(&jar, "http://api.localhost/").is_empty());
+    }
+
+    #[test]
+    fn test_path_matching() {
+        let jar = CookieJar::new();
+        jar.set_cookie(&url("https://example.com/account/login"), "default=1");
+        jar.set_cookie(&url("https://example.com/"), "root=1; Path=/");
+        jar.set_cookie(&url("https://example.com/"), "api=1; Path=/api");
+
+        assert_eq!(
+            names(&jar, "https://example.com/account/settings"),
+            ["default", "root"]
+        );
+        assert_eq!(
+            names(&jar, "https://example.com/api/users"),
+            ["api", "root"]
+        );
+        assert_eq!(names(&jar, "https://example.com/apiv2"), ["root"]);
+    }
+
+    #[test]
+    fn test_secure_and_expiry() {
+        let jar = CookieJar::new();
+        let origin = url("https://example.com/");
+        jar.set_cookie(&origin, "secure=1; Secure; HttpOnly");
+        jar.set_cookie(&origin, "expired=1; Expires=Thu, 01 Jan 1970 00:00:00 GMT");
+        jar.set_cookie(&origin, "session=1; Max-Age=3600");
+
+        assert_eq!(names(&jar, "https://example.com/"), ["secure", "session"]);
+        assert_eq!(names(&jar, "http://example.com/"), ["session"]);
+
+        // A cookie is replaced by name, domain and path, and deleted by expiring it
+        jar.set_cookie(&origin, "session=2");
+        assert_eq!(
+            jar.cookies(&origin),
+            [
+                ("secure".to_string(), "1".to_string()),
+                ("session".to_string(), "2".to_string())
+            ]
+        );
+        jar.set_cookie(&origin, "session=; Max-Age=0");
+        assert_eq!(names(&jar, "https://example.com/"), ["secure"]);
+    }
+
+    #[test]
+    fn test_cookie_header() {
+        let jar = CookieJar::new();
+        let origin = url("https://example.com/");
+        let mut response = HeaderMap::new();
+        response.append(SET_COOKIE, HeaderValue::from_static("a=1"));
+        response.append(SET_COOKIE, HeaderValue::from_static("b=2; Path=/"));
+        jar.store_response(&origin, &response);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("manual=0"));
+        jar.add_cookie_header(&origin, &mut headers);
+        assert_eq!(headers[COOKIE], "manual=0; a=1; b=2");
+    }
+
+    #[test]
+    fn test_persistence() {
+        let path =
+            std::env::temp_dir().join(format!("calleen-cookies-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let origin = url("https://example.com/");
+
+        let jar = CookieJar::persistent(&path).unwrap();
+        jar.set_cookie(&origin, "session=abc");
+        jar.set_cookie(&origin, "short=1; Max-Age=3600");
+
+        let reloaded = CookieJar::persistent(&path).unwrap();
+        assert_eq!(
+            names(&reloaded, "https://example.com/"),
+            ["session", "short"]
+        );
+
+        reloaded.clear();
+        assert!(CookieJar::persistent(&path)
+            .unwrap()
+            .cookies(&origin)
+            .is_empty());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            CookieJar::persistent(&path),
+            Err(Error::ConfigurationError(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_saved_in_the_background() {
+        let path = std::env::temp_dir().join(format!(
+            "calleen-cookies-background-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let origin = url("https://example.com/");
+
+        let jar = CookieJar::persistent(&path).unwrap();
+        for value in ["a=1", "a=2", "a=3"] {
+            let mut response = HeaderMap::new();
+            response.insert(SET_COOKIE, HeaderValue::from_static(value));
+            jar.store_response(&origin, &response);
+        }
+
+        // The last change is saved, even if earlier writes finish after it
+        let reloaded = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(reloaded) = CookieJar::persistent(&path) {
+                    if reloaded.cookies(&origin) == [("a".to_string(), "3".to_string())] {
+                        return reloaded;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(reloaded.is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+//! Hostname resolution for the default transport.
+//!
+//! By default, hostnames are resolved with the system resolver. Use
+//! [`ClientBuilder::resolve`](crate::ClientBuilder::resolve) to pin a hostname to
+//! fixed addresses, for example to point a production hostname at a local
+//! server in tests, or implement [`Resolve`] and pass it to
+//! [`ClientBuilder::dns_resolver`](crate::ClientBuilder::dns_resolver) to resolve
+//! hostnames yourself.
+//!
+//! Failures to resolve a hostname are reported as an [`Error::Network`](crate::Error::Network)
+//! of kind [`NetworkErrorKind::Dns`](crate::NetworkErrorKind::Dns).
+//!
+//! # Examples
+//!
+//! ```
+//! use calleen::dns::{Resolve, Resolving, SystemResolver};
+//! use std::collections::HashMap;
+//! use std::net::SocketAddr;
+//! use std::sync::{Arc, Mutex};
+//!
+//! /// Remembers every address the system resolver returns.
+//! #[derive(Default)]
+//! struct CachingResolver {
+//!     cache: Arc<Mutex<HashMap<String, Vec<SocketAddr>>>>,
+//! }
+//!
+//! impl Resolve for CachingResolver {
+//!     fn resolve(&self, host: &str) -> Resolving {
+//!         let cached = self.cache.lock().unwrap().get(host).cloned();
+//!         let cache = self.cache.clone();
+//!         let host = host.to_string();
+//!         Box::pin(async move {
+//!             if let Some(addrs) = cached {
+//!                 return Ok(addrs);
+//!             }
+//!             let addrs = SystemResolver.resolve(&host).await?;
+//!             cache.lock().unwrap().insert(host, addrs.clone());
+//!             Ok(addrs)
+//!         })
+//!     }
+//! }
+//!
+//! # fn example() -> Result<(), calleen::Error> {
+//! let client = calleen::Client::builder()
+//!     .base_url("https://api.example.com")?
+//!     .dns_resolver(Box::new(CachingResolver::default()))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+/// The future returned by [`Resolve::resolve`].
+pub type Resolving = Pin<Box<dyn Future<Output = io::Result<Vec<SocketAddr>>> + Send>>;
+
+/// Resolves hostnames to socket addresses.
+///
+/// The port of each address is replaced by the port in the request URL, if it
+/// has one, and otherwise a port of `0` is replaced by the scheme's default port.
+pub trait Resolve: Send + Sync {
+    /// Resolves `host` to one or more addresses to try in order.
+    fn resolve(&self, host: &str) -> Resolving;
+}
+
+/// Resolves hostnames with the operating system's resolver.
+///
+/// This is the resolver used by default, and is useful to wrap in a [`Resolve`]
+/// implementation that adds caching or rewrites hostnames.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, host: &str) -> Resolving {
+        let host = host.to_string();
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host.as_str(), 0)).await?;
+            Ok(addrs.collect())
+        })
+    }
+}
+
+/// A failure to resolve a hostname, found in the source chain of transport
+/// errors to tell DNS errors apart from other connection errors.
+#[derive(Debug)]
+pub(crate) struct DnsError {
+    host: String,
+    source: io::Error,
+}
+
+impl std::fmt::Display for DnsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to resolve {}", self.host)
+    }
+}
+
+impl std::error::Error for DnsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Adapts static overrides and a [`Resolve`] implementation to reqwest.
+pub(crate) struct ReqwestResolver {
+    overrides: HashMap<String, Vec<SocketAddr>>,
+    resolver: Box<dyn Resolve>,
+}
+
+impl ReqwestResolver {
+    pub(crate) fn new(
+        overrides: HashMap<String, Vec<SocketAddr>>,
+        resolver: Option<Box<dyn Resolve>>,
+    ) -> Self {
+        Self {
+            overrides,
+            resolver: resolver.unwrap_or_else(|| Box::new(SystemResolver)),
+        }
+    }
+}
+
+impl reqwest::dns::Resolve for ReqwestResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        let resolving = match self.overrides.get(&host.to_ascii_lowercase()) {
+            Some(addrs) => {
+                let addrs = addrs.clone();
+                Box::pin(async move { Ok(addrs) })
+            }
+            None => self.resolver.resolve(&host),
+        };
+
+        Box::pin(async move {
+            let error = match resolving.await {
+                Ok(addrs) if !addrs.is_empty() => {
+                    return Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs);
+                }
+                Ok(_) => io::Error::new(io::ErrorKind::NotFound, "no addresses found"),
+                Err(e) => e,
+            };
+            Err(Box::new(DnsError {
+                host,
+                source: error,
+            }) as Box<dyn std::error::Error + Send + Sync>)
+        })
+    }
+}
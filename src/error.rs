@@ -4,6 +4,7 @@
 //! while remaining ergonomic to use. All errors include context about what went wrong and
 //! provide access to raw response data when available.
 
+use crate::{Attempt, RequestSummary};
 use http::{HeaderMap, StatusCode};
 
 /// The main error type for HTTP API calls.
@@ -39,27 +40,39 @@ use http::{HeaderMap, StatusCode};
 pub enum Error {
     /// A network-level error occurred (connection failed, DNS lookup failed, etc.).
     ///
-    /// This wraps the underlying `reqwest::Error` and indicates problems at the network layer
-    /// rather than the HTTP protocol layer.
+    /// This indicates problems at the network layer rather than the HTTP protocol layer.
+    /// The underlying error, if any, is available through [`std::error::Error::source`].
     #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(#[source] NetworkError),
 
     /// The request timed out.
     ///
     /// This occurs when the request takes longer than the configured timeout duration.
+    /// Each attempt is timed separately.
+    ///
+    /// # Fields
+    ///
+    /// * `request` - The request that was in flight when the attempt timed out
     #[error("Request timed out")]
-    Timeout,
+    Timeout {
+        /// The request that timed out (boxed to reduce error size)
+        request: Option<Box<RequestSummary>>,
+    },
 
     /// Failed to deserialize the response body into the expected type.
     ///
     /// This error preserves both the raw response text and the serde error message,
-    /// making it easy to debug deserialization issues in production.
+    /// making it easy to debug deserialization issues in production. Fields masked
+    /// by the client's [`RedactionPolicy`](crate::redact::RedactionPolicy) are
+    /// replaced in the raw response, and it's truncated to the policy's
+    /// `max_body_len`.
     ///
     /// # Fields
     ///
     /// * `raw_response` - The raw response body as a string
     /// * `serde_error` - The error message from serde
     /// * `status` - The HTTP status code of the response
+    /// * `request` - The request that produced the response, if it came from one
     #[error("Failed to deserialize response (status {status}): {serde_error}")]
     DeserializationFailed {
         /// The raw response body that failed to deserialize
@@ -68,11 +81,16 @@ pub enum Error {
         serde_error: String,
         /// The HTTP status code
         status: StatusCode,
+        /// The request that produced the response (boxed to reduce error size)
+        request: Option<Box<RequestSummary>>,
     },
 
     /// The server returned a non-2xx HTTP status code.
     ///
-    /// This error includes the full response details for debugging.
+    /// This error includes the full response details for debugging. The body and
+    /// headers have the client's [`RedactionPolicy`](crate::redact::RedactionPolicy)
+    /// applied, so sensitive fields and headers are masked and the body is
+    /// truncated to the policy's `max_body_len`.
     ///
     /// # Fields
     ///
@@ -80,6 +98,8 @@ pub enum Error {
     /// * `raw_response` - The raw response body (boxed to reduce error size)
     /// * `headers` - The response headers (boxed to reduce error size)
     /// * `rate_limit_info` - Rate limit information if available (especially for 429 responses)
+    /// * `request` - The request that produced the response, including any
+    ///   redirects that led to it
     #[error("HTTP error {status}: {raw_response}")]
     HttpError {
         /// The HTTP status code
@@ -90,6 +110,29 @@ pub enum Error {
         headers: Box<HeaderMap>,
         /// Rate limit information parsed from headers
         rate_limit_info: Option<crate::rate_limit::RateLimitInfo>,
+        /// The request that produced the response (boxed to reduce error size)
+        request: Option<Box<RequestSummary>>,
+    },
+
+    /// The server asked for a wait longer than the configured maximum.
+    ///
+    /// This is only returned when
+    /// [`RateLimitConfig::max_wait_policy`](crate::rate_limit::RateLimitConfig::max_wait_policy)
+    /// is [`MaxWaitPolicy::Fail`](crate::rate_limit::MaxWaitPolicy::Fail). No
+    /// request is retried, and requests sharing the rate limit fail the same way
+    /// until it resets.
+    ///
+    /// # Fields
+    ///
+    /// * `retry_after` - How long until the rate limit resets
+    /// * `request` - The request that was rate limited, or `None` if it failed
+    ///   without being sent because the rate limit was already in effect
+    #[error("Rate limited, retry after {retry_after:?}")]
+    RateLimited {
+        /// How long until the rate limit resets
+        retry_after: std::time::Duration,
+        /// The request that was rate limited (boxed to reduce error size)
+        request: Option<Box<RequestSummary>>,
     },
 
     /// Invalid configuration was provided.
@@ -108,12 +151,15 @@ pub enum Error {
     ///
     /// * `attempts` - The number of retry attempts made
     /// * `last_error` - The last error encountered before giving up
+    /// * `attempt_history` - Every attempt made, in order
     #[error("Max retries exceeded after {attempts} attempts: {last_error}")]
     MaxRetriesExceeded {
         /// The number of attempts made
         attempts: usize,
         /// The last error encountered
         last_error: Box<Error>,
+        /// Every attempt made, in order, ending with the one that failed last
+        attempt_history: Vec<Attempt>,
     },
 
     /// Failed to serialize the request body.
@@ -146,6 +192,7 @@ impl Error {
     ///     raw_response: "Server error".to_string().into_boxed_str(),
     ///     headers: Box::new(http::HeaderMap::new()),
     ///     rate_limit_info: None,
+    ///     request: None,
     /// };
     ///
     /// assert!(err.is_retryable());
@@ -156,6 +203,7 @@ impl Error {
     ///     raw_response: "Rate limited".to_string().into_boxed_str(),
     ///     headers: Box::new(http::HeaderMap::new()),
     ///     rate_limit_info: None,
+    ///     request: None,
     /// };
     ///
     /// assert!(err.is_retryable());
@@ -166,6 +214,7 @@ impl Error {
     ///     raw_response: "Bad request".to_string().into_boxed_str(),
     ///     headers: Box::new(http::HeaderMap::new()),
     ///     rate_limit_info: None,
+    ///     request: None,
     /// };
     ///
     /// assert!(!err.is_retryable());
@@ -173,13 +222,14 @@ impl Error {
     pub fn is_retryable(&self) -> bool {
         match self {
             Error::Network(_) => true,
-            Error::Timeout => true,
+            Error::Timeout { .. } => true,
             Error::HttpError { status, .. } => {
                 // 5xx errors are always retryable
                 // 429 (Too Many Requests) is also retryable
                 status.is_server_error() || status.as_u16() == 429
             }
             Error::DeserializationFailed { .. } => false,
+            Error::RateLimited { .. } => false,
             Error::ConfigurationError(_) => false,
             Error::MaxRetriesExceeded { .. } => false,
             Error::SerializationFailed(_) => false,
@@ -223,6 +273,60 @@ impl Error {
         }
     }
 
+    /// Returns the URL an `HttpError` response came from, after following any
+    /// redirects.
+    pub fn final_url(&self) -> Option<&url::Url> {
+        match self {
+            Error::HttpError { request, .. } => request.as_ref().map(|request| &request.url),
+            _ => None,
+        }
+    }
+
+    /// Returns the URLs that redirected to an `HttpError` response, in the
+    /// order they were requested.
+    ///
+    /// This is empty for other errors and responses that weren't redirected.
+    pub fn redirects(&self) -> &[url::Url] {
+        match self {
+            Error::HttpError {
+                request: Some(request),
+                ..
+            } => &request.redirects,
+            _ => &[],
+        }
+    }
+
+    /// Returns the request that produced this error, if it came from a network
+    /// exchange.
+    ///
+    /// This is available for `HttpError`, `DeserializationFailed`, `Network`,
+    /// `Timeout` and `RateLimited` errors, and for the last error of
+    /// `MaxRetriesExceeded`.
+    pub fn request(&self) -> Option<&RequestSummary> {
+        match self {
+            Error::HttpError { request, .. } => request.as_deref(),
+            Error::DeserializationFailed { request, .. } => request.as_deref(),
+            Error::Timeout { request } => request.as_deref(),
+            Error::RateLimited { request, .. } => request.as_deref(),
+            Error::Network(error) => error.request(),
+            Error::MaxRetriesExceeded { last_error, .. } => last_error.request(),
+            _ => None,
+        }
+    }
+
+    /// Returns every attempt made before giving up, in order.
+    ///
+    /// This is only available for `MaxRetriesExceeded`, and is empty for other
+    /// errors.
+    pub fn attempt_history(&self) -> &[Attempt] {
+        match self {
+            Error::MaxRetriesExceeded {
+                attempt_history, ..
+            } => attempt_history,
+            _ => &[],
+        }
+    }
+
     /// Returns the recommended delay from rate limit information.
     ///
     /// This is a convenience method that extracts the delay from rate limit info
@@ -232,6 +336,105 @@ impl Error {
     }
 }
 
+impl From<NetworkError> for Error {
+    fn from(error: NetworkError) -> Self {
+        Error::Network(error)
+    }
+}
+
+/// What went wrong in a [`NetworkError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetworkErrorKind {
+    /// The server's hostname couldn't be resolved to an address.
+    Dns,
+    /// The connection to the server couldn't be established, e.g. it was refused.
+    Connect,
+    /// The request failed while it was being sent, e.g. the connection was reset.
+    Request,
+    /// The response body couldn't be read.
+    Body,
+    /// Any other network-level failure.
+    Other,
+}
+
+/// A network-level error, stored in [`Error::Network`].
+///
+/// # Examples
+///
+/// ```
+/// use calleen::{NetworkError, NetworkErrorKind};
+///
+/// let err = NetworkError::new(NetworkErrorKind::Connect, "connection refused");
+/// assert!(err.is_connect());
+/// assert_eq!(err.to_string(), "connection refused");
+/// ```
+#[derive(Debug)]
+pub struct NetworkError {
+    kind: NetworkErrorKind,
+    message: String,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    request: Option<Box<RequestSummary>>,
+}
+
+impl NetworkError {
+    /// Creates a network error of the given kind.
+    pub fn new(kind: NetworkErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            source: None,
+            request: None,
+        }
+    }
+
+    /// Sets the underlying error that caused this one.
+    pub fn with_source(mut self, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Sets the request that failed.
+    pub(crate) fn with_request(mut self, request: RequestSummary) -> Self {
+        self.request = Some(Box::new(request));
+        self
+    }
+
+    /// Returns what went wrong.
+    pub fn kind(&self) -> NetworkErrorKind {
+        self.kind
+    }
+
+    /// Returns `true` if the connection to the server couldn't be established.
+    pub fn is_connect(&self) -> bool {
+        self.kind == NetworkErrorKind::Connect
+    }
+
+    /// Returns `true` if the server's hostname couldn't be resolved.
+    pub fn is_dns(&self) -> bool {
+        self.kind == NetworkErrorKind::Dns
+    }
+
+    /// Returns the request that failed, if the error came from the client
+    /// sending one.
+    pub fn request(&self) -> Option<&RequestSummary> {
+        self.request.as_deref()
+    }
+}
+
+impl std::fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for NetworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
 /// A specialized `Result` type for HTTP API calls.
 ///
 /// This is a convenience alias for `Result<T, Error>`.
@@ -0,0 +1,234 @@
+//! Hedged requests for reducing tail latency.
+//!
+//! When hedging is enabled, the client sends a duplicate of an idempotent request
+//! if the original hasn't produced a response within the hedge delay. Whichever
+//! request answers first wins and the others are cancelled.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Number of recent latency samples kept for percentile-based hedge delays.
+const LATENCY_WINDOW: usize = 1000;
+
+/// Minimum number of samples required before percentiles are trusted.
+const MIN_SAMPLES: usize = 20;
+
+/// Determines how long to wait before sending a hedged request.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::hedge::HedgeDelay;
+/// use std::time::Duration;
+///
+/// // Always hedge after 50ms
+/// let fixed = HedgeDelay::Fixed(Duration::from_millis(50));
+///
+/// // Hedge once the request is slower than 95% of recent requests
+/// let adaptive = HedgeDelay::Percentile {
+///     percentile: 95.0,
+///     fallback: Duration::from_millis(100),
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub enum HedgeDelay {
+    /// Hedge after a fixed delay.
+    Fixed(Duration),
+
+    /// Hedge after the given percentile of recently observed latencies.
+    ///
+    /// Latencies are measured on the original request of each attempt, from
+    /// sending it until the response headers are received. If a hedged request
+    /// answers first, the time the original had been in flight when it was
+    /// cancelled is recorded instead, so slow requests aren't left out.
+    Percentile {
+        /// The latency percentile to hedge at, between 0 and 100.
+        percentile: f64,
+        /// The delay to use until enough latencies have been observed.
+        fallback: Duration,
+    },
+}
+
+/// Configuration for hedged requests.
+///
+/// Hedging only applies to idempotent methods (GET, HEAD, PUT, DELETE,
+/// OPTIONS and TRACE), since duplicates of other requests could have side effects.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::hedge::{HedgeConfig, HedgeDelay};
+/// use std::time::Duration;
+///
+/// let config = HedgeConfig::builder()
+///     .delay(HedgeDelay::Fixed(Duration::from_millis(50)))
+///     .max_hedges(2)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HedgeConfig {
+    /// How long to wait before sending each hedged request.
+    ///
+    /// Defaults to the 95th percentile of observed latencies, falling back
+    /// to 100ms until enough samples have been collected.
+    pub delay: HedgeDelay,
+
+    /// The maximum number of hedged requests sent for a single attempt, in
+    /// addition to the original request.
+    ///
+    /// A hedged request that fails isn't replaced, so this also caps how many
+    /// are in flight at once.
+    ///
+    /// Defaults to `1`.
+    pub max_hedges: usize,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            delay: HedgeDelay::Percentile {
+                percentile: 95.0,
+                fallback: Duration::from_millis(100),
+            },
+            max_hedges: 1,
+        }
+    }
+}
+
+impl HedgeConfig {
+    /// Creates a new builder for configuring hedged requests.
+    pub fn builder() -> HedgeConfigBuilder {
+        HedgeConfigBuilder::default()
+    }
+
+    /// Returns the delay to wait before hedging, based on the observed latencies.
+    pub(crate) fn delay(&self, latencies: &LatencyTracker) -> Duration {
+        match &self.delay {
+            HedgeDelay::Fixed(delay) => *delay,
+            HedgeDelay::Percentile {
+                percentile,
+                fallback,
+            } => latencies.percentile(*percentile).unwrap_or(*fallback),
+        }
+    }
+}
+
+/// Builder for `HedgeConfig`.
+#[derive(Default)]
+pub struct HedgeConfigBuilder {
+    delay: Option<HedgeDelay>,
+    max_hedges: Option<usize>,
+}
+
+impl HedgeConfigBuilder {
+    /// Sets how long to wait before sending a hedged request.
+    pub fn delay(mut self, delay: HedgeDelay) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Sets the maximum number of hedged requests sent per attempt.
+    pub fn max_hedges(mut self, max_hedges: usize) -> Self {
+        self.max_hedges = Some(max_hedges);
+        self
+    }
+
+    /// Builds the `HedgeConfig`.
+    pub fn build(self) -> HedgeConfig {
+        let default = HedgeConfig::default();
+        HedgeConfig {
+            delay: self.delay.unwrap_or(default.delay),
+            max_hedges: self.max_hedges.unwrap_or(default.max_hedges),
+        }
+    }
+}
+
+/// A sliding window of recently observed request latencies.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyTracker {
+    samples: Mutex<VecDeque<Duration>>,
+}
+
+impl LatencyTracker {
+    /// Records a latency sample, evicting the oldest one if the window is full.
+    pub(crate) fn record(&self, latency: Duration) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        if samples.len() == LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+
+    /// Returns the given percentile of the recorded latencies, or `None` if
+    /// too few samples have been recorded.
+    pub(crate) fn percentile(&self, percentile: f64) -> Option<Duration> {
+        let mut sorted: Vec<Duration> = {
+            let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+            if samples.len() < MIN_SAMPLES {
+                return None;
+            }
+            samples.iter().copied().collect()
+        };
+        sorted.sort_unstable();
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64).round();
+        sorted.get(rank as usize).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_requires_min_samples() {
+        let tracker = LatencyTracker::default();
+        for _ in 0..MIN_SAMPLES - 1 {
+            tracker.record(Duration::from_millis(10));
+        }
+        assert_eq!(tracker.percentile(95.0), None);
+
+        tracker.record(Duration::from_millis(10));
+        assert_eq!(tracker.percentile(95.0), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_percentile_values() {
+        let tracker = LatencyTracker::default();
+        for ms in 1..=100 {
+            tracker.record(Duration::from_millis(ms));
+        }
+
+        assert_eq!(tracker.percentile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(tracker.percentile(50.0), Some(Duration::from_millis(51)));
+        assert_eq!(tracker.percentile(95.0), Some(Duration::from_millis(95)));
+        assert_eq!(tracker.percentile(100.0), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_window_evicts_oldest_samples() {
+        let tracker = LatencyTracker::default();
+        for _ in 0..LATENCY_WINDOW {
+            tracker.record(Duration::from_secs(10));
+        }
+        for _ in 0..LATENCY_WINDOW {
+            tracker.record(Duration::from_millis(1));
+        }
+
+        assert_eq!(tracker.percentile(100.0), Some(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn test_config_delay_falls_back() {
+        let config = HedgeConfig::default();
+        let tracker = LatencyTracker::default();
+        assert_eq!(config.delay(&tracker), Duration::from_millis(100));
+
+        let config = HedgeConfig::builder()
+            .delay(HedgeDelay::Fixed(Duration::from_millis(20)))
+            .build();
+        assert_eq!(config.delay(&tracker), Duration::from_millis(20));
+        assert_eq!(config.max_hedges, 1);
+    }
+}
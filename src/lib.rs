@@ -62,9 +62,31 @@
 //! - **Flexible retry logic** - Exponential backoff, linear, or custom retry strategies
 //! - **Customizable retry predicates** - Retry on 5xx, timeouts, network errors, or custom conditions
 //! - **Automatic logging** - Structured logging with `tracing` for observability
+//! - **Redaction** - Keep credentials and personal data out of logs and errors
+//! - **Log levels** - Tune log verbosity per response class and status code
+//! - **Expected statuses** - Treat chosen non-2xx statuses as results rather than errors
+//! - **Pagination** - Stream items across pages using Link headers, cursors, offsets or page numbers
+//! - **Metrics** - Request counts, latency histograms, retries and rate limit waits
+//!   through a pluggable recorder or the `metrics` facade (requires the `metrics` feature)
+//! - **OpenTelemetry tracing** - Spans per call and attempt with W3C/B3 context propagation
+//!   (requires the `opentelemetry` feature)
 //! - **Response metadata** - Access latency, status codes, headers, retry attempts, and raw response bodies
+//! - **Hedged requests** - Duplicate slow idempotent requests to cut tail latency
+//! - **Load balancing** - Spread calls across replicas with health-based ejection and failover
+//! - **Request signing** - Sign every attempt with AWS SigV4 or a configurable HMAC
+//! - **Cookies** - Keep session cookies in a jar, optionally persisted to a JSON file
+//! - **Redirects** - Cap redirects, guard credentials across origins and see where a response came from
+//! - **Compression** - Decompress gzip, deflate, brotli and zstd responses and compress
+//!   large request bodies (requires the `gzip`, `deflate`, `brotli` or `zstd` features)
+//! - **Pluggable transport** - Send requests through reqwest or your own `Transport`
+//! - **TLS** - Private root CAs, client certificates for mutual TLS and key pinning
+//! - **Unix sockets** - Call local daemons that serve HTTP over a Unix domain socket
+//! - **DNS overrides** - Pin hostnames to fixed addresses or plug in your own resolver
+//! - **Mock transport** - Script responses, network errors and timeouts in unit tests
+//!   (requires the `test-util` feature)
 //! - **Builder pattern** - Fluent API for configuring clients
-//! - **Connection pooling** - Reusable clients with efficient connection management
+//! - **Connection pooling** - Reusable clients with efficient connection management,
+//!   with tunable pools, keepalive, HTTP/2 and proxies
 //!
 //! ## Error Handling
 //!
@@ -79,7 +101,7 @@
 //!     Ok(response) => {
 //!         println!("Success: {:?}", response.data);
 //!     }
-//!     Err(Error::DeserializationFailed { raw_response, serde_error, status }) => {
+//!     Err(Error::DeserializationFailed { raw_response, serde_error, status, .. }) => {
 //!         eprintln!("Failed to deserialize (status {}):", status);
 //!         eprintln!("  Raw response: {}", raw_response);
 //!         eprintln!("  Error: {}", serde_error);
@@ -121,14 +143,33 @@
 //! # }
 //! ```
 
+pub mod balance;
 mod client;
+pub mod compression;
+pub mod connection;
+pub mod cookie;
+pub mod dns;
 mod error;
+pub mod hedge;
+pub mod logging;
 pub mod metadata;
+pub mod metrics;
+#[cfg(feature = "test-util")]
+pub mod mock;
+pub mod pagination;
 pub mod rate_limit;
+pub mod redact;
+pub mod redirect;
 mod response;
 pub mod retry;
+pub mod signing;
+#[cfg(feature = "opentelemetry")]
+pub mod telemetry;
+pub mod tls;
+pub mod transport;
 
 pub use client::{Client, ClientBuilder};
-pub use error::{Error, Result};
-pub use response::Response;
+pub use either::Either;
+pub use error::{Error, NetworkError, NetworkErrorKind, Result};
+pub use response::{Attempt, RequestSummary, Response, Timings};
 pub use retry::{RetryPredicate, RetryStrategy};
@@ -0,0 +1,370 @@
+//! Log verbosity configuration.
+//!
+//! By default, calleen logs 4xx responses and deserialization failures at `error`
+//! level (typically actionable), and 5xx responses and failed attempts at `warn` level
+//! (typically not). [`LogConfig`] changes these levels, per status or kind of
+//! failure, silences expected statuses, and optionally logs outgoing requests at
+//! `debug` level.
+
+use crate::{metadata::RequestMetadata, Error};
+use http::StatusCode;
+use std::collections::HashMap;
+
+/// The level to log an event at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogLevel {
+    /// Don't log the event.
+    Off,
+    /// Log at `error` level.
+    Error,
+    /// Log at `warn` level.
+    Warn,
+    /// Log at `info` level.
+    Info,
+    /// Log at `debug` level.
+    Debug,
+    /// Log at `trace` level.
+    Trace,
+}
+
+/// Logs a `tracing` event at a level chosen at runtime.
+macro_rules! log_at {
+    ($level:expr, $($arg:tt)+) => {
+        match $level {
+            $crate::logging::LogLevel::Off => {}
+            $crate::logging::LogLevel::Error => tracing::error!($($arg)+),
+            $crate::logging::LogLevel::Warn => tracing::warn!($($arg)+),
+            $crate::logging::LogLevel::Info => tracing::info!($($arg)+),
+            $crate::logging::LogLevel::Debug => tracing::debug!($($arg)+),
+            $crate::logging::LogLevel::Trace => tracing::trace!($($arg)+),
+        }
+    };
+}
+
+pub(crate) use log_at;
+
+/// Configuration for what calleen logs, and at which level.
+///
+/// Per-status levels take precedence over the levels for response classes and
+/// failed attempts, and apply to every log message about a response with that
+/// status. Individual requests can override them further with
+/// [`RequestMetadata::with_log_level`]. Attempts that fail without a response
+/// are logged at the level for their kind of failure, if one is set, and at
+/// `attempt_failure` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::logging::{LogConfig, LogLevel};
+///
+/// let config = LogConfig::builder()
+///     // 404s are expected for this client, don't log them at all
+///     .status_level(404, LogLevel::Off)
+///     // Conflicts are handled by the caller, keep them out of error logs
+///     .status_level(409, LogLevel::Info)
+///     .server_error(LogLevel::Error)
+///     // Connection errors are retried, so only show them in debug logs
+///     .network_error(LogLevel::Debug)
+///     .log_request_body(true)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    /// The level for every received response. Defaults to `Info`.
+    pub response: LogLevel,
+
+    /// The level for 4xx responses. Defaults to `Error`.
+    pub client_error: LogLevel,
+
+    /// The level for 5xx responses. Defaults to `Warn`.
+    pub server_error: LogLevel,
+
+    /// The level for response bodies that fail to deserialize. Defaults to `Error`.
+    pub deserialization_failure: LogLevel,
+
+    /// The level for failed attempts, including network errors and timeouts.
+    /// Defaults to `Warn`.
+    pub attempt_failure: LogLevel,
+
+    /// The level for attempts that fail with a network error, overriding
+    /// `attempt_failure`. Defaults to `None`.
+    pub network_error: Option<LogLevel>,
+
+    /// The level for attempts that time out, overriding `attempt_failure`.
+    /// Defaults to `None`.
+    pub timeout: Option<LogLevel>,
+
+    /// The level for attempts that fail because a rate limit resets later than
+    /// the configured maximum wait, overriding `attempt_failure`. Defaults to `None`.
+    pub rate_limited: Option<LogLevel>,
+
+    /// Levels for specific status codes, overriding the levels above.
+    pub status_levels: HashMap<u16, LogLevel>,
+
+    /// Whether to log the headers sent with each request, including those of
+    /// followed redirects, after redaction, at `debug` level. Defaults to `false`.
+    pub log_request_headers: bool,
+
+    /// Whether to log request bodies, after redaction, at `debug` level.
+    /// Defaults to `false`.
+    pub log_request_body: bool,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            response: LogLevel::Info,
+            client_error: LogLevel::Error,
+            server_error: LogLevel::Warn,
+            deserialization_failure: LogLevel::Error,
+            attempt_failure: LogLevel::Warn,
+            network_error: None,
+            timeout: None,
+            rate_limited: None,
+            status_levels: HashMap::new(),
+            log_request_headers: false,
+            log_request_body: false,
+        }
+    }
+}
+
+impl LogConfig {
+    /// Creates a new builder for configuring logging.
+    pub fn builder() -> LogConfigBuilder {
+        LogConfigBuilder {
+            config: Self::default(),
+        }
+    }
+
+    /// Returns the level configured for a status, preferring the request's overrides.
+    fn status_level(&self, metadata: &RequestMetadata, status: StatusCode) -> Option<LogLevel> {
+        metadata
+            .log_levels
+            .get(&status.as_u16())
+            .or_else(|| self.status_levels.get(&status.as_u16()))
+            .copied()
+    }
+
+    /// Returns the level for the "Received HTTP response" message.
+    pub(crate) fn response_level(
+        &self,
+        metadata: &RequestMetadata,
+        status: StatusCode,
+    ) -> LogLevel {
+        self.status_level(metadata, status).unwrap_or(self.response)
+    }
+
+    /// Returns the level for the message describing a non-2xx response.
+    pub(crate) fn error_status_level(
+        &self,
+        metadata: &RequestMetadata,
+        status: StatusCode,
+    ) -> LogLevel {
+        self.status_level(metadata, status).unwrap_or_else(|| {
+            if status.is_server_error() {
+                self.server_error
+            } else if status.is_client_error() {
+                self.client_error
+            } else {
+                LogLevel::Off
+            }
+        })
+    }
+
+    /// Returns the level for a response body that failed to deserialize.
+    pub(crate) fn deserialization_level(
+        &self,
+        metadata: &RequestMetadata,
+        status: StatusCode,
+    ) -> LogLevel {
+        self.status_level(metadata, status)
+            .unwrap_or(self.deserialization_failure)
+    }
+
+    /// Returns the level for the "Request failed" message.
+    pub(crate) fn attempt_failure_level(
+        &self,
+        metadata: &RequestMetadata,
+        error: &Error,
+    ) -> LogLevel {
+        let kind_level = match error {
+            Error::Network(_) => self.network_error,
+            Error::Timeout { .. } => self.timeout,
+            Error::RateLimited { .. } => self.rate_limited,
+            _ => None,
+        };
+        error
+            .status()
+            .and_then(|status| self.status_level(metadata, status))
+            .or(kind_level)
+            .unwrap_or(self.attempt_failure)
+    }
+}
+
+/// Builder for `LogConfig`.
+pub struct LogConfigBuilder {
+    config: LogConfig,
+}
+
+impl LogConfigBuilder {
+    /// Sets the level for every received response.
+    pub fn response(mut self, level: LogLevel) -> Self {
+        self.config.response = level;
+        self
+    }
+
+    /// Sets the level for 4xx responses.
+    pub fn client_error(mut self, level: LogLevel) -> Self {
+        self.config.client_error = level;
+        self
+    }
+
+    /// Sets the level for 5xx responses.
+    pub fn server_error(mut self, level: LogLevel) -> Self {
+        self.config.server_error = level;
+        self
+    }
+
+    /// Sets the level for response bodies that fail to deserialize.
+    pub fn deserialization_failure(mut self, level: LogLevel) -> Self {
+        self.config.deserialization_failure = level;
+        self
+    }
+
+    /// Sets the level for failed attempts.
+    pub fn attempt_failure(mut self, level: LogLevel) -> Self {
+        self.config.attempt_failure = level;
+        self
+    }
+
+    /// Sets the level for attempts that fail with a network error.
+    pub fn network_error(mut self, level: LogLevel) -> Self {
+        self.config.network_error = Some(level);
+        self
+    }
+
+    /// Sets the level for attempts that time out.
+    pub fn timeout(mut self, level: LogLevel) -> Self {
+        self.config.timeout = Some(level);
+        self
+    }
+
+    /// Sets the level for attempts that fail because of a rate limit.
+    pub fn rate_limited(mut self, level: LogLevel) -> Self {
+        self.config.rate_limited = Some(level);
+        self
+    }
+
+    /// Sets the level for every message about responses with the given status.
+    pub fn status_level(mut self, status: u16, level: LogLevel) -> Self {
+        self.config.status_levels.insert(status, level);
+        self
+    }
+
+    /// Sets whether to log request headers at `debug` level.
+    pub fn log_request_headers(mut self, enabled: bool) -> Self {
+        self.config.log_request_headers = enabled;
+        self
+    }
+
+    /// Sets whether to log request bodies at `debug` level.
+    pub fn log_request_body(mut self, enabled: bool) -> Self {
+        self.config.log_request_body = enabled;
+        self
+    }
+
+    /// Builds the `LogConfig`.
+    pub fn build(self) -> LogConfig {
+        self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::Method;
+
+    #[test]
+    fn test_default_levels() {
+        let config = LogConfig::default();
+        let metadata = RequestMetadata::new(Method::GET, "/test");
+
+        assert_eq!(
+            config.error_status_level(&metadata, StatusCode::NOT_FOUND),
+            LogLevel::Error
+        );
+        assert_eq!(
+            config.error_status_level(&metadata, StatusCode::BAD_GATEWAY),
+            LogLevel::Warn
+        );
+        assert_eq!(
+            config.response_level(&metadata, StatusCode::OK),
+            LogLevel::Info
+        );
+        assert_eq!(
+            config.attempt_failure_level(&metadata, &Error::Timeout { request: None }),
+            LogLevel::Warn
+        );
+    }
+
+    #[test]
+    fn test_status_overrides() {
+        let config = LogConfig::builder()
+            .status_level(404, LogLevel::Off)
+            .status_level(409, LogLevel::Info)
+            .build();
+        let metadata = RequestMetadata::new(Method::GET, "/test");
+
+        assert_eq!(
+            config.error_status_level(&metadata, StatusCode::NOT_FOUND),
+            LogLevel::Off
+        );
+        assert_eq!(
+            config.response_level(&metadata, StatusCode::NOT_FOUND),
+            LogLevel::Off
+        );
+        assert_eq!(
+            config.error_status_level(&metadata, StatusCode::CONFLICT),
+            LogLevel::Info
+        );
+
+        // Request overrides take precedence over the client's
+        let metadata = metadata.with_log_level(404, LogLevel::Warn);
+        assert_eq!(
+            config.error_status_level(&metadata, StatusCode::NOT_FOUND),
+            LogLevel::Warn
+        );
+    }
+
+    #[test]
+    fn test_failure_kind_levels() {
+        let config = LogConfig::builder()
+            .attempt_failure(LogLevel::Error)
+            .network_error(LogLevel::Debug)
+            .timeout(LogLevel::Info)
+            .build();
+        let metadata = RequestMetadata::new(Method::GET, "/test");
+
+        let network = Error::Network(crate::NetworkError::new(
+            crate::NetworkErrorKind::Connect,
+            "connection refused",
+        ));
+        assert_eq!(
+            config.attempt_failure_level(&metadata, &network),
+            LogLevel::Debug
+        );
+        assert_eq!(
+            config.attempt_failure_level(&metadata, &Error::Timeout { request: None }),
+            LogLevel::Info
+        );
+        // Kinds without a level of their own use the level for failed attempts
+        let rate_limited = Error::RateLimited {
+            retry_after: std::time::Duration::from_secs(60),
+            request: None,
+        };
+        assert_eq!(
+            config.attempt_failure_level(&metadata, &rate_limited),
+            LogLevel::Error
+        );
+    }
+}
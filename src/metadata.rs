@@ -1,13 +1,20 @@
 //! Request metadata and configuration types.
 
+use crate::{logging::LogLevel, redact::REDACTED};
 use http::{HeaderMap, HeaderName, HeaderValue, Method};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 /// Metadata for an individual HTTP request.
 ///
 /// This type contains all the configuration needed to make a single HTTP request,
 /// including headers, query parameters, method, and path.
-#[derive(Debug, Clone)]
+///
+/// Metadata doesn't know which headers a client's
+/// [`RedactionPolicy`](crate::redact::RedactionPolicy) considers sensitive, so
+/// the `Debug` output only shows the values of a few standard headers, such as
+/// `Accept` and `Content-Type`, and redacts the rest.
+#[derive(Clone)]
 pub struct RequestMetadata {
     /// The HTTP method (GET, POST, etc.).
     pub method: Method,
@@ -20,6 +27,23 @@ pub struct RequestMetadata {
 
     /// Query parameters for this request.
     pub query_params: HashMap<String, String>,
+
+    /// The endpoint template (e.g. `/users/{id}`) used to label metrics.
+    ///
+    /// When not set, the request path is used instead.
+    pub endpoint: Option<String>,
+
+    /// Log levels for specific status codes, overriding the client's
+    /// [`LogConfig`](crate::logging::LogConfig) for this request.
+    pub log_levels: HashMap<u16, LogLevel>,
+
+    /// Non-2xx statuses that are treated as a successful response instead of
+    /// an [`Error::HttpError`](crate::Error::HttpError).
+    pub accepted_statuses: HashSet<u16>,
+
+    /// The rate limit bucket of this request, overriding the client's
+    /// [`RateLimitScope`](crate::rate_limit::RateLimitScope).
+    pub rate_limit_bucket: Option<String>,
 }
 
 impl RequestMetadata {
@@ -30,6 +54,10 @@ impl RequestMetadata {
             path: path.into(),
             headers: HeaderMap::new(),
             query_params: HashMap::new(),
+            endpoint: None,
+            log_levels: HashMap::new(),
+            accepted_statuses: HashSet::new(),
+            rate_limit_bucket: None,
         }
     }
 
@@ -63,6 +91,130 @@ impl RequestMetadata {
         self.query_params.extend(params);
         self
     }
+
+    /// Sets the endpoint template used to label metrics for this request.
+    ///
+    /// Use a template rather than the concrete path when the path contains
+    /// IDs, so that metrics don't get a label per ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calleen::metadata::RequestMetadata;
+    /// use http::Method;
+    ///
+    /// let metadata = RequestMetadata::new(Method::GET, "/users/123")
+    ///     .with_endpoint("/users/{id}");
+    /// ```
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Sets the level for log messages about responses with the given status.
+    ///
+    /// This overrides the client's [`LogConfig`](crate::logging::LogConfig) for
+    /// this request only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calleen::{logging::LogLevel, metadata::RequestMetadata};
+    /// use http::Method;
+    ///
+    /// // A missing user is expected here, so don't log it
+    /// let metadata = RequestMetadata::new(Method::GET, "/users/123")
+    ///     .with_log_level(404, LogLevel::Off);
+    /// ```
+    pub fn with_log_level(mut self, status: u16, level: LogLevel) -> Self {
+        self.log_levels.insert(status, level);
+        self
+    }
+
+    /// Treats a non-2xx status as a successful response.
+    ///
+    /// Responses with an accepted status are deserialized like a 2xx response,
+    /// aren't retried, and aren't logged as errors. This is useful for APIs
+    /// where, for example, a 404 means "absent" rather than a failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calleen::metadata::RequestMetadata;
+    /// use http::Method;
+    ///
+    /// let metadata = RequestMetadata::new(Method::DELETE, "/sessions/123")
+    ///     .accept_status(404)
+    ///     .accept_status(410);
+    /// ```
+    pub fn accept_status(mut self, status: u16) -> Self {
+        self.accepted_statuses.insert(status);
+        self
+    }
+
+    /// Puts the request in a named rate limit bucket.
+    ///
+    /// Once any request in a bucket is rate limited, every request in the bucket
+    /// waits until the rate limit resets. Use this for APIs that rate limit
+    /// endpoints separately, or that share a limit across several hosts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calleen::metadata::RequestMetadata;
+    /// use http::Method;
+    ///
+    /// let metadata = RequestMetadata::new(Method::GET, "/search")
+    ///     .with_rate_limit_bucket("search");
+    /// ```
+    pub fn with_rate_limit_bucket(mut self, bucket: impl Into<String>) -> Self {
+        self.rate_limit_bucket = Some(bucket.into());
+        self
+    }
+}
+
+impl fmt::Debug for RequestMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestMetadata")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("headers", &DebugHeaders(&self.headers))
+            .field("query_params", &self.query_params)
+            .field("endpoint", &self.endpoint)
+            .field("log_levels", &self.log_levels)
+            .field("accepted_statuses", &self.accepted_statuses)
+            .field("rate_limit_bucket", &self.rate_limit_bucket)
+            .finish()
+    }
+}
+
+/// Headers whose values are shown in the `Debug` output of `RequestMetadata`.
+const DEBUG_HEADERS: [&str; 7] = [
+    "accept",
+    "accept-encoding",
+    "accept-language",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "user-agent",
+];
+
+/// Formats headers, redacting every value but those of [`DEBUG_HEADERS`].
+struct DebugHeaders<'a>(&'a HeaderMap);
+
+impl fmt::Debug for DebugHeaders<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(name, value)| {
+                let value = if DEBUG_HEADERS.contains(&name.as_str()) {
+                    value.to_str().unwrap_or(REDACTED)
+                } else {
+                    REDACTED
+                };
+                (name.as_str(), value)
+            }))
+            .finish()
+    }
 }
 
 impl Default for RequestMetadata {
@@ -0,0 +1,223 @@
+//! Metrics for HTTP calls.
+//!
+//! Implement [`MetricsRecorder`] and install it with
+//! [`ClientBuilder::metrics_recorder`](crate::ClientBuilder::metrics_recorder) to
+//! collect request counts, latencies, retries and rate limit waits. With the `metrics`
+//! cargo feature, [`FacadeRecorder`] forwards everything to the
+//! [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! Every metric is labelled with the request method and endpoint. The endpoint is the
+//! template set with [`RequestMetadata::with_endpoint`], falling back to the request
+//! path. Set a template for paths containing IDs to keep label cardinality low.
+
+use crate::metadata::RequestMetadata;
+use http::{Method, StatusCode};
+use std::time::Duration;
+
+/// Labels attached to every metric, taken from the request's [`RequestMetadata`].
+#[derive(Debug, Clone, Copy)]
+pub struct MetricLabels<'a> {
+    /// The HTTP method.
+    pub method: &'a Method,
+
+    /// The endpoint template (e.g. `/users/{id}`), or the request path if no
+    /// template was set.
+    pub endpoint: &'a str,
+}
+
+impl<'a> From<&'a RequestMetadata> for MetricLabels<'a> {
+    fn from(metadata: &'a RequestMetadata) -> Self {
+        Self {
+            method: &metadata.method,
+            endpoint: metadata.endpoint.as_deref().unwrap_or(&metadata.path),
+        }
+    }
+}
+
+/// Trait for recording metrics about HTTP calls.
+///
+/// All methods have empty default implementations, so implementors only need
+/// to override the metrics they care about.
+///
+/// A `status` of `None` means no HTTP response was received, for example
+/// because of a network error or timeout.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::metrics::{MetricLabels, MetricsRecorder};
+/// use http::StatusCode;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::time::Duration;
+///
+/// #[derive(Default)]
+/// struct ErrorCounter(AtomicU64);
+///
+/// impl MetricsRecorder for ErrorCounter {
+///     fn record_call(&self, _labels: &MetricLabels<'_>, status: Option<StatusCode>, _latency: Duration) {
+///         if !status.is_some_and(|s| s.is_success()) {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+/// ```
+pub trait MetricsRecorder: Send + Sync {
+    /// Records a completed logical call, including all of its retries.
+    ///
+    /// `latency` is the total time spent in the call, including retry delays.
+    fn record_call(
+        &self,
+        labels: &MetricLabels<'_>,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        let _ = (labels, status, latency);
+    }
+
+    /// Records a single attempt of a call.
+    ///
+    /// `latency` covers sending the request and reading the response body.
+    fn record_attempt(
+        &self,
+        labels: &MetricLabels<'_>,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        let _ = (labels, status, latency);
+    }
+
+    /// Records that a failed attempt is about to be retried.
+    ///
+    /// `attempt` is the number of the attempt that failed (1-indexed).
+    fn record_retry(&self, labels: &MetricLabels<'_>, attempt: usize) {
+        let _ = (labels, attempt);
+    }
+
+    /// Records time spent waiting because of a rate limit.
+    fn record_rate_limit_wait(&self, labels: &MetricLabels<'_>, wait: Duration) {
+        let _ = (labels, wait);
+    }
+
+    /// Records a response body that failed to deserialize.
+    fn record_deserialization_failure(&self, labels: &MetricLabels<'_>, status: StatusCode) {
+        let _ = (labels, status);
+    }
+}
+
+/// Returns the label value for an optional status code.
+///
+/// This is the numeric status code, or `"none"` if no response was received.
+pub fn status_label(status: Option<StatusCode>) -> String {
+    match status {
+        Some(status) => status.as_str().to_string(),
+        None => "none".to_string(),
+    }
+}
+
+/// A [`MetricsRecorder`] that forwards to the [`metrics`](https://docs.rs/metrics) facade.
+///
+/// The following metrics are emitted, all labelled with `method` and `endpoint`:
+///
+/// - `calleen_requests_total` (counter, also labelled with `status`)
+/// - `calleen_request_duration_seconds` (histogram, also labelled with `status`)
+/// - `calleen_attempt_duration_seconds` (histogram, also labelled with `status`)
+/// - `calleen_retries_total` (counter)
+/// - `calleen_rate_limit_wait_seconds` (histogram)
+/// - `calleen_deserialization_failures_total` (counter, also labelled with `status`)
+///
+/// # Examples
+///
+/// ```no_run
+/// use calleen::{Client, metrics::FacadeRecorder};
+///
+/// # async fn example() -> Result<(), calleen::Error> {
+/// let client = Client::builder()
+///     .base_url("https://api.example.com")?
+///     .metrics_recorder(Box::new(FacadeRecorder))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FacadeRecorder;
+
+#[cfg(feature = "metrics")]
+impl MetricsRecorder for FacadeRecorder {
+    fn record_call(
+        &self,
+        labels: &MetricLabels<'_>,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        let labels = [
+            ("method", labels.method.to_string()),
+            ("endpoint", labels.endpoint.to_string()),
+            ("status", status_label(status)),
+        ];
+        ::metrics::counter!("calleen_requests_total", &labels).increment(1);
+        ::metrics::histogram!("calleen_request_duration_seconds", &labels).record(latency);
+    }
+
+    fn record_attempt(
+        &self,
+        labels: &MetricLabels<'_>,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        let labels = [
+            ("method", labels.method.to_string()),
+            ("endpoint", labels.endpoint.to_string()),
+            ("status", status_label(status)),
+        ];
+        ::metrics::histogram!("calleen_attempt_duration_seconds", &labels).record(latency);
+    }
+
+    fn record_retry(&self, labels: &MetricLabels<'_>, _attempt: usize) {
+        let labels = [
+            ("method", labels.method.to_string()),
+            ("endpoint", labels.endpoint.to_string()),
+        ];
+        ::metrics::counter!("calleen_retries_total", &labels).increment(1);
+    }
+
+    fn record_rate_limit_wait(&self, labels: &MetricLabels<'_>, wait: Duration) {
+        let labels = [
+            ("method", labels.method.to_string()),
+            ("endpoint", labels.endpoint.to_string()),
+        ];
+        ::metrics::histogram!("calleen_rate_limit_wait_seconds", &labels).record(wait);
+    }
+
+    fn record_deserialization_failure(&self, labels: &MetricLabels<'_>, status: StatusCode) {
+        let labels = [
+            ("method", labels.method.to_string()),
+            ("endpoint", labels.endpoint.to_string()),
+            ("status", status.as_str().to_string()),
+        ];
+        ::metrics::counter!("calleen_deserialization_failures_total", &labels).increment(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_prefer_endpoint_template() {
+        let metadata = RequestMetadata::new(Method::GET, "/users/123");
+        let labels = MetricLabels::from(&metadata);
+        assert_eq!(labels.endpoint, "/users/123");
+
+        let metadata = metadata.with_endpoint("/users/{id}");
+        let labels = MetricLabels::from(&metadata);
+        assert_eq!(labels.endpoint, "/users/{id}");
+        assert_eq!(labels.method, Method::GET);
+    }
+
+    #[test]
+    fn test_status_label() {
+        assert_eq!(status_label(Some(StatusCode::NOT_FOUND)), "404");
+        assert_eq!(status_label(None), "none");
+    }
+}
@@ -0,0 +1,439 @@
+//! A mock transport for testing code that uses a [`Client`](crate::Client).
+//!
+//! [`MockTransport`] replaces the network with scripted responses. Register a
+//! [`Mock`] for every endpoint the code under test calls, pass the transport to
+//! [`ClientBuilder::mock_transport`](crate::ClientBuilder::mock_transport), and
+//! call [`MockTransport::verify`] at the end of the test to check that every
+//! endpoint was called as often as expected.
+//!
+//! Network errors and timeouts can be scripted like any other response. Retry
+//! delays, rate limit waits, response delays and the client's timeout all use
+//! tokio's clock, so with `#[tokio::test(start_paused = true)]` a test with long
+//! backoffs completes instantly.
+//!
+//! This module requires the `test-util` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use calleen::{Client, NetworkErrorKind, RetryStrategy};
+//! use calleen::mock::{Mock, MockResponse, MockTransport};
+//! use http::Method;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), calleen::Error> {
+//! let transport = MockTransport::new();
+//! transport.register(
+//!     Mock::new(Method::GET, "/orders")
+//!         .query("status", "open")
+//!         .respond_with(MockResponse::network_error(NetworkErrorKind::Request, "connection reset"))
+//!         .respond_with(MockResponse::new(503))
+//!         .respond_with(MockResponse::json(200, &serde_json::json!([])))
+//!         .expect(3),
+//! );
+//!
+//! let client = Client::builder()
+//!     .base_url("https://api.example.com")?
+//!     .retry_strategy(RetryStrategy::Linear {
+//!         delay: Duration::from_secs(10),
+//!         max_retries: 3,
+//!     })
+//!     .mock_transport(transport.clone())
+//!     .build()?;
+//!
+//! let mut metadata = calleen::metadata::RequestMetadata::new(Method::GET, "/orders");
+//! metadata.query_params.insert("status".to_string(), "open".to_string());
+//! let orders = client.call::<(), Vec<serde_json::Value>>(metadata, None).await?;
+//! assert_eq!(orders.attempts, 3);
+//!
+//! transport.verify();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{NetworkError, NetworkErrorKind},
+    transport::{Transport, TransportFuture},
+    Error,
+};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+/// A transport that answers requests with scripted responses.
+///
+/// Clones share the same mocks and recorded requests, so a clone can be passed
+/// to the client while the test keeps another to verify the calls.
+///
+/// Each request is answered by the first registered [`Mock`] that matches it.
+/// A request no mock matches fails with [`Error::ConfigurationError`], and is
+/// reported by [`verify`](MockTransport::verify).
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    mocks: Vec<Mock>,
+    requests: Vec<RecordedRequest>,
+    unmatched: Vec<String>,
+}
+
+impl MockTransport {
+    /// Creates a transport with no mocks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a mock.
+    ///
+    /// Mocks are matched in the order they were registered.
+    pub fn register(&self, mock: Mock) {
+        self.state().mocks.push(mock);
+    }
+
+    /// Returns every request sent through the transport, in the order they were sent.
+    ///
+    /// This includes requests no mock matched.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Checks that every mock was called as often as expected, and that every
+    /// request matched a mock.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a description of every unmet expectation and unmatched request.
+    pub fn verify(&self) {
+        let state = self.state();
+        let mut failures = Vec::new();
+
+        for mock in &state.mocks {
+            if let Some(expected) = mock.expected_calls {
+                if mock.calls != expected {
+                    failures.push(format!(
+                        "{} {} was called {} times, expected {}",
+                        mock.method, mock.path, mock.calls, expected
+                    ));
+                }
+            }
+        }
+        for request in &state.unmatched {
+            failures.push(format!("no mock matched {}", request));
+        }
+
+        if !failures.is_empty() {
+            panic!("mock verification failed:\n  {}", failures.join("\n  "));
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+        let (parts, body) = request.into_parts();
+        let request = RecordedRequest {
+            method: parts.method,
+            url: Url::parse(&parts.uri.to_string()).expect("request URI is a valid URL"),
+            headers: parts.headers,
+            body,
+        };
+
+        let response = {
+            let mut state = self.state();
+            state.requests.push(request.clone());
+            match state.mocks.iter_mut().find(|mock| mock.matches(&request)) {
+                Some(mock) => Ok(mock.next_response()),
+                None => {
+                    let description = format!("{} {}", request.method, request.url);
+                    state.unmatched.push(description.clone());
+                    Err(Error::ConfigurationError(format!(
+                        "No mock matches {}",
+                        description
+                    )))
+                }
+            }
+        };
+
+        Box::pin(async move {
+            let response = response?;
+            if !response.delay.is_zero() {
+                tokio::time::sleep(response.delay).await;
+            }
+
+            match response.outcome {
+                Outcome::Response {
+                    status,
+                    headers,
+                    body,
+                } => {
+                    let mut response = http::Response::new(body);
+                    *response.status_mut() = status;
+                    *response.headers_mut() = headers;
+                    Ok(response)
+                }
+                Outcome::NetworkError { kind, message } => {
+                    Err(NetworkError::new(kind, message).into())
+                }
+                Outcome::Timeout => Err(Error::Timeout { request: None }),
+            }
+        })
+    }
+}
+
+/// A scripted endpoint: which requests it matches and how it answers them.
+///
+/// A mock matches a request with the same method and path, and every query
+/// parameter, header and body it was configured with. The request may have
+/// other query parameters and headers.
+///
+/// Responses are returned in the order they were added, and the last one is
+/// repeated once the others are used up. A mock without responses answers
+/// with an empty `200 OK`.
+#[derive(Debug, Clone)]
+pub struct Mock {
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: Option<BodyMatcher>,
+    responses: Vec<MockResponse>,
+    expected_calls: Option<usize>,
+    calls: usize,
+}
+
+#[derive(Debug, Clone)]
+enum BodyMatcher {
+    Json(serde_json::Value),
+    Bytes(Vec<u8>),
+}
+
+impl Mock {
+    /// Creates a mock for requests with the given method and path.
+    pub fn new(method: Method, path: impl Into<String>) -> Self {
+        Self {
+            method,
+            path: path.into(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            responses: Vec::new(),
+            expected_calls: None,
+            calls: 0,
+        }
+    }
+
+    /// Only matches requests with the given query parameter.
+    pub fn query(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// Only matches requests with the given header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the header name or value is invalid.
+    pub fn header(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        let name = HeaderName::try_from(name.as_ref()).expect("invalid header name");
+        let value = HeaderValue::try_from(value.as_ref()).expect("invalid header value");
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Only matches requests whose body is JSON equal to `body`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `body` can't be serialized to JSON.
+    pub fn json_body(mut self, body: &impl Serialize) -> Self {
+        let body = serde_json::to_value(body).expect("mock body must serialize to JSON");
+        self.body = Some(BodyMatcher::Json(body));
+        self
+    }
+
+    /// Only matches requests whose body is exactly `body`.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(BodyMatcher::Bytes(body.into()));
+        self
+    }
+
+    /// Adds a response to the end of the script.
+    pub fn respond_with(mut self, response: MockResponse) -> Self {
+        self.responses.push(response);
+        self
+    }
+
+    /// Expects the mock to be called exactly `calls` times.
+    ///
+    /// This is checked by [`MockTransport::verify`].
+    pub fn expect(mut self, calls: usize) -> Self {
+        self.expected_calls = Some(calls);
+        self
+    }
+
+    fn matches(&self, request: &RecordedRequest) -> bool {
+        if request.method != self.method || request.url.path() != self.path {
+            return false;
+        }
+
+        let query_matches = self.query.iter().all(|(name, value)| {
+            request
+                .url
+                .query_pairs()
+                .any(|(n, v)| n == name.as_str() && v == value.as_str())
+        });
+        let headers_match = self
+            .headers
+            .iter()
+            .all(|(name, value)| request.headers.get_all(name).iter().any(|v| v == value));
+        let body_matches = match &self.body {
+            Some(BodyMatcher::Json(body)) => request
+                .json::<serde_json::Value>()
+                .is_ok_and(|request_body| request_body == *body),
+            Some(BodyMatcher::Bytes(body)) => request.body == *body,
+            None => true,
+        };
+
+        query_matches && headers_match && body_matches
+    }
+
+    fn next_response(&mut self) -> MockResponse {
+        self.calls += 1;
+        let index = (self.calls - 1).min(self.responses.len().saturating_sub(1));
+        self.responses
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| MockResponse::new(200))
+    }
+}
+
+/// A scripted answer to a request: a response, a network error or a timeout.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    outcome: Outcome,
+    delay: Duration,
+}
+
+#[derive(Debug, Clone)]
+enum Outcome {
+    Response {
+        status: StatusCode,
+        headers: HeaderMap,
+        body: Vec<u8>,
+    },
+    NetworkError {
+        kind: NetworkErrorKind,
+        message: String,
+    },
+    Timeout,
+}
+
+impl MockResponse {
+    /// Creates an empty response with the given status.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` isn't a valid HTTP status code.
+    pub fn new(status: u16) -> Self {
+        Self {
+            outcome: Outcome::Response {
+                status: StatusCode::from_u16(status).expect("invalid status code"),
+                headers: HeaderMap::new(),
+                body: Vec::new(),
+            },
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Creates a response with the given status and JSON body.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` isn't a valid HTTP status code or `body` can't be
+    /// serialized to JSON.
+    pub fn json(status: u16, body: &impl Serialize) -> Self {
+        let body = serde_json::to_vec(body).expect("mock body must serialize to JSON");
+        Self::new(status)
+            .header("content-type", "application/json")
+            .body(body)
+    }
+
+    /// Fails the request with an [`Error::Network`] of the given kind.
+    pub fn network_error(kind: NetworkErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            outcome: Outcome::NetworkError {
+                kind,
+                message: message.into(),
+            },
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Fails the request with [`Error::Timeout`], as though the client's
+    /// timeout had elapsed.
+    ///
+    /// To test the client's timeout itself, [`delay`](MockResponse::delay) a
+    /// response by longer than the timeout instead.
+    pub fn timeout() -> Self {
+        Self {
+            outcome: Outcome::Timeout,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Adds a response header. This has no effect on network errors and timeouts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the header name or value is invalid.
+    pub fn header(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        if let Outcome::Response { headers, .. } = &mut self.outcome {
+            let name = HeaderName::try_from(name.as_ref()).expect("invalid header name");
+            let value = HeaderValue::try_from(value.as_ref()).expect("invalid header value");
+            headers.append(name, value);
+        }
+        self
+    }
+
+    /// Sets the response body. This has no effect on network errors and timeouts.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        if let Outcome::Response { body: b, .. } = &mut self.outcome {
+            *b = body.into();
+        }
+        self
+    }
+
+    /// Waits for `delay` before answering, measured on tokio's clock.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request sent through a [`MockTransport`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// The request method.
+    pub method: Method,
+    /// The full request URL, including the query string.
+    pub url: Url,
+    /// The request headers.
+    pub headers: HeaderMap,
+    /// The request body.
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Deserializes the request body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+}
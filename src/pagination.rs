@@ -0,0 +1,329 @@
+//! Pagination across multiple responses.
+//!
+//! [`Client::paginate`](crate::Client::paginate) fetches the pages of a paginated
+//! endpoint one after another and yields the items of every page as a single
+//! [`Stream`](futures_util::Stream). Each page is fetched with
+//! [`Client::call`](crate::Client::call), so retries and rate limiting apply to
+//! every page individually.
+
+use crate::{metadata::RequestMetadata, Response};
+use serde_json::Value;
+use url::Url;
+
+/// How the next page of a paginated endpoint is requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageScheme {
+    /// Follow the `rel="next"` link of the RFC 8288 `Link` response header.
+    ///
+    /// Pagination ends when a response has no next link. Only the path and query
+    /// of the link are used; the request is always sent to the client's base URL.
+    Link,
+
+    /// Pass a cursor taken from the response body as a query parameter.
+    ///
+    /// Pagination ends when the cursor is missing, `null` or an empty string.
+    Cursor {
+        /// A JSON pointer (RFC 6901) to the next cursor in the response body,
+        /// e.g. `/meta/next_cursor`.
+        cursor: String,
+        /// The query parameter to send the cursor in, e.g. `cursor`.
+        param: String,
+    },
+
+    /// Pass an offset and a limit as query parameters.
+    ///
+    /// Pagination ends when a page has fewer than `limit` items.
+    Offset {
+        /// The query parameter for the offset, e.g. `offset`.
+        offset_param: String,
+        /// The query parameter for the limit, e.g. `limit`.
+        limit_param: String,
+        /// The number of items to request per page.
+        limit: usize,
+    },
+
+    /// Pass an incrementing page number as a query parameter.
+    ///
+    /// Pagination ends when a page has no items.
+    PageNumber {
+        /// The query parameter for the page number, e.g. `page`.
+        param: String,
+        /// The number of the first page, usually `0` or `1`.
+        first_page: usize,
+    },
+}
+
+/// Configuration for paginating an endpoint.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::pagination::{PageScheme, PaginationConfig};
+///
+/// // GET /orders?cursor=... returning {"data": [...], "meta": {"next_cursor": "..."}}
+/// let config = PaginationConfig::builder()
+///     .scheme(PageScheme::Cursor {
+///         cursor: "/meta/next_cursor".to_string(),
+///         param: "cursor".to_string(),
+///     })
+///     .items("/data")
+///     .max_pages(50)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PaginationConfig {
+    /// How the next page is requested. Defaults to [`PageScheme::Link`].
+    pub scheme: PageScheme,
+
+    /// A JSON pointer (RFC 6901) to the array of items in each response body.
+    ///
+    /// `None` (the default) means the response body itself is the array.
+    pub items: Option<String>,
+
+    /// The maximum number of pages to fetch. Defaults to 100.
+    ///
+    /// The stream ends without an error once this many pages have been fetched.
+    pub max_pages: usize,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            scheme: PageScheme::Link,
+            items: None,
+            max_pages: 100,
+        }
+    }
+}
+
+impl PaginationConfig {
+    /// Creates a new builder for configuring pagination.
+    pub fn builder() -> PaginationConfigBuilder {
+        PaginationConfigBuilder {
+            config: Self::default(),
+        }
+    }
+
+    /// Returns the metadata for the first page.
+    pub(crate) fn first_page(&self, mut metadata: RequestMetadata) -> RequestMetadata {
+        match &self.scheme {
+            PageScheme::Link | PageScheme::Cursor { .. } => {}
+            PageScheme::Offset {
+                offset_param,
+                limit_param,
+                limit,
+            } => {
+                metadata
+                    .query_params
+                    .insert(offset_param.clone(), "0".to_string());
+                metadata
+                    .query_params
+                    .insert(limit_param.clone(), limit.to_string());
+            }
+            PageScheme::PageNumber { param, first_page } => {
+                metadata
+                    .query_params
+                    .insert(param.clone(), first_page.to_string());
+            }
+        }
+        metadata
+    }
+
+    /// Returns the items of a page, or `None` if the body has no array of items.
+    pub(crate) fn items<'a>(&self, body: &'a Value) -> Option<&'a Vec<Value>> {
+        match &self.items {
+            Some(pointer) => body.pointer(pointer)?.as_array(),
+            None => body.as_array(),
+        }
+    }
+
+    /// Returns the metadata for the page after `response`, or `None` if it was
+    /// the last page.
+    ///
+    /// `url` is the URL `response` was fetched from, which relative links are
+    /// resolved against.
+    pub(crate) fn next_page(
+        &self,
+        mut metadata: RequestMetadata,
+        url: &Url,
+        response: &Response<Value>,
+        items: usize,
+    ) -> Option<RequestMetadata> {
+        match &self.scheme {
+            PageScheme::Link => {
+                let link = response
+                    .headers
+                    .get_all(http::header::LINK)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .find_map(next_link)?;
+                let next = url.join(&link).ok()?;
+
+                metadata.path = next.path().to_string();
+                metadata.query_params = next.query_pairs().into_owned().collect();
+                Some(metadata)
+            }
+            PageScheme::Cursor { cursor, param } => {
+                let cursor = match response.data.pointer(cursor)? {
+                    Value::String(cursor) if cursor.is_empty() => return None,
+                    Value::String(cursor) => cursor.clone(),
+                    Value::Number(cursor) => cursor.to_string(),
+                    _ => return None,
+                };
+
+                metadata.query_params.insert(param.clone(), cursor);
+                Some(metadata)
+            }
+            PageScheme::Offset {
+                offset_param,
+                limit,
+                ..
+            } => {
+                if items < *limit {
+                    return None;
+                }
+
+                let offset = metadata
+                    .query_params
+                    .get(offset_param)
+                    .and_then(|offset| offset.parse::<usize>().ok())
+                    .unwrap_or(0);
+                metadata
+                    .query_params
+                    .insert(offset_param.clone(), (offset + items).to_string());
+                Some(metadata)
+            }
+            PageScheme::PageNumber { param, first_page } => {
+                if items == 0 {
+                    return None;
+                }
+
+                let page = metadata
+                    .query_params
+                    .get(param)
+                    .and_then(|page| page.parse::<usize>().ok())
+                    .unwrap_or(*first_page);
+                metadata
+                    .query_params
+                    .insert(param.clone(), (page + 1).to_string());
+                Some(metadata)
+            }
+        }
+    }
+}
+
+/// Builder for `PaginationConfig`.
+pub struct PaginationConfigBuilder {
+    config: PaginationConfig,
+}
+
+impl PaginationConfigBuilder {
+    /// Sets how the next page is requested.
+    pub fn scheme(mut self, scheme: PageScheme) -> Self {
+        self.config.scheme = scheme;
+        self
+    }
+
+    /// Sets the JSON pointer to the array of items in each response body.
+    pub fn items(mut self, pointer: impl Into<String>) -> Self {
+        self.config.items = Some(pointer.into());
+        self
+    }
+
+    /// Sets the maximum number of pages to fetch.
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.config.max_pages = max_pages;
+        self
+    }
+
+    /// Builds the `PaginationConfig`.
+    pub fn build(self) -> PaginationConfig {
+        self.config
+    }
+}
+
+/// Returns the target of the `rel="next"` link in an RFC 8288 `Link` header value.
+fn next_link(header: &str) -> Option<String> {
+    let mut rest = header;
+
+    loop {
+        let start = rest.find('<')?;
+        let end = start + rest[start..].find('>')?;
+        let target = &rest[start + 1..end];
+
+        // Parameters run until the next link, which starts with `<`
+        let params_end = rest[end..].find('<').map_or(rest.len(), |i| end + i);
+        let params = &rest[end + 1..params_end];
+
+        let is_next = params.split(';').any(|param| {
+            let Some((name, value)) = param.split_once('=') else {
+                return false;
+            };
+            name.trim().eq_ignore_ascii_case("rel")
+                && value
+                    .trim()
+                    .trim_end_matches(',')
+                    .trim_matches('"')
+                    .split_ascii_whitespace()
+                    .any(|rel| rel.eq_ignore_ascii_case("next"))
+        });
+        if is_next {
+            return Some(target.to_string());
+        }
+
+        rest = &rest[params_end..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_link() {
+        assert_eq!(
+            next_link(r#"<https://api.example.com/items?page=2>; rel="next""#),
+            Some("https://api.example.com/items?page=2".to_string())
+        );
+        assert_eq!(
+            next_link(
+                r#"</items?page=1>; rel="prev", </items?page=3>; rel="next", </items?page=9>; rel="last""#
+            ),
+            Some("/items?page=3".to_string())
+        );
+        assert_eq!(
+            next_link(r#"</items?a=1,2>; title="x"; rel="last next""#),
+            Some("/items?a=1,2".to_string())
+        );
+        assert_eq!(next_link("</items?page=9>; rel=last"), None);
+        assert_eq!(next_link(""), None);
+    }
+
+    #[test]
+    fn test_offset_next_page() {
+        let config = PaginationConfig::builder()
+            .scheme(PageScheme::Offset {
+                offset_param: "offset".to_string(),
+                limit_param: "limit".to_string(),
+                limit: 2,
+            })
+            .build();
+        let url = Url::parse("https://api.example.com/items").unwrap();
+        let response = Response::new(
+            Value::Null,
+            String::new(),
+            http::StatusCode::OK,
+            http::HeaderMap::new(),
+            std::time::Duration::ZERO,
+            1,
+        );
+
+        let first = config.first_page(RequestMetadata::new(http::Method::GET, "/items"));
+        assert_eq!(first.query_params["offset"], "0");
+        assert_eq!(first.query_params["limit"], "2");
+
+        let second = config.next_page(first, &url, &response, 2).unwrap();
+        assert_eq!(second.query_params["offset"], "2");
+        assert!(config.next_page(second, &url, &response, 1).is_none());
+    }
+}
@@ -2,17 +2,28 @@
 //!
 //! This module provides automatic rate limit handling by parsing common
 //! rate limit headers from HTTP responses and respecting the indicated wait times.
+//!
+//! Rate limits are shared: once a request is rate limited, every new or retrying
+//! request to the same host (or in the same [`RateLimitScope`] bucket) waits until
+//! the rate limit resets.
 
+use crate::{metadata::RequestMetadata, Error, Result};
 use http::HeaderMap;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+use url::Url;
 
 /// Information extracted from rate limit headers.
 ///
 /// This struct contains parsed rate limit data from various standard and
-/// common rate limit headers.
-#[derive(Debug, Clone)]
+/// common rate limit headers. See [`RateLimitDialect`] for the headers that
+/// are understood.
+#[derive(Debug, Clone, Default)]
 pub struct RateLimitInfo {
-    /// When the rate limit resets (from X-RateLimit-Reset or RateLimit-Reset headers).
+    /// When the rate limit resets (from `X-RateLimit-Reset`, `RateLimit-Reset` or
+    /// an equivalent header).
     pub reset_at: Option<SystemTime>,
 
     /// How long to wait before retrying (from Retry-After header).
@@ -20,16 +31,23 @@ pub struct RateLimitInfo {
 
     /// Number of requests remaining in the current window.
     pub remaining: Option<u64>,
+
+    /// Number of requests allowed in each window.
+    pub limit: Option<u64>,
+
+    /// The length of the rate limit window (from `RateLimit-Policy`).
+    pub window: Option<Duration>,
 }
 
 impl RateLimitInfo {
     /// Extracts rate limit information from HTTP response headers.
     ///
-    /// Parses common rate limit headers including:
+    /// This is [`RateLimitInfo::parse`] with [`RateLimitDialect::Auto`], which
+    /// parses common rate limit headers including:
     /// - `Retry-After` (standard HTTP, seconds or HTTP date)
-    /// - `X-RateLimit-Reset` (Unix timestamp)
-    /// - `RateLimit-Reset` (draft standard, Unix timestamp)
-    /// - `X-RateLimit-Remaining`
+    /// - `RateLimit`, `RateLimit-Policy` and `RateLimit-*` (IETF draft)
+    /// - `X-RateLimit-*` and `X-Rate-Limit-*`
+    /// - `X-Shopify-Shop-Api-Call-Limit`
     ///
     /// # Examples
     ///
@@ -45,15 +63,62 @@ impl RateLimitInfo {
     /// assert!(info.retry_after.is_some());
     /// ```
     pub fn from_headers(headers: &HeaderMap) -> Self {
-        let retry_after = parse_retry_after(headers);
-        let reset_at = parse_rate_limit_reset(headers);
-        let remaining = parse_rate_limit_remaining(headers);
+        Self::parse(headers, &RateLimitDialect::Auto)
+    }
 
-        Self {
-            reset_at,
-            retry_after,
-            remaining,
+    /// Extracts rate limit information from HTTP response headers, using the
+    /// given header dialect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calleen::rate_limit::{RateLimitDialect, RateLimitInfo};
+    /// use http::HeaderMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("ratelimit", r#""default";r=0;t=30"#.parse().unwrap());
+    /// headers.insert("ratelimit-policy", r#""default";q=100;w=60"#.parse().unwrap());
+    ///
+    /// let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Ietf);
+    /// assert_eq!(info.remaining, Some(0));
+    /// assert_eq!(info.limit, Some(100));
+    /// assert_eq!(info.window, Some(Duration::from_secs(60)));
+    /// ```
+    pub fn parse(headers: &HeaderMap, dialect: &RateLimitDialect) -> Self {
+        let mut info = Self {
+            retry_after: parse_retry_after(headers),
+            ..Self::default()
+        };
+
+        match dialect {
+            RateLimitDialect::Auto => {
+                info.merge_ietf(headers, ResetFormat::Auto);
+                let reset_format = ResetFormat::Auto;
+                info.merge_headers(headers, &CustomRateLimitHeaders::x_ratelimit(reset_format));
+                info.merge_headers(headers, &CustomRateLimitHeaders::x_rate_limit(reset_format));
+                info.merge_shopify(headers);
+            }
+            RateLimitDialect::Ietf => info.merge_ietf(headers, ResetFormat::DeltaSeconds),
+            RateLimitDialect::GitHub => info.merge_headers(
+                headers,
+                &CustomRateLimitHeaders::x_ratelimit(ResetFormat::UnixSeconds),
+            ),
+            RateLimitDialect::Twitter => info.merge_headers(
+                headers,
+                &CustomRateLimitHeaders::x_rate_limit(ResetFormat::UnixSeconds),
+            ),
+            RateLimitDialect::Stripe => {}
+            RateLimitDialect::Shopify => info.merge_shopify(headers),
+            RateLimitDialect::Custom(custom) => {
+                if let Some(name) = &custom.retry_after {
+                    info.retry_after = header(headers, name).and_then(parse_delay);
+                }
+                info.merge_headers(headers, custom);
+            }
         }
+
+        info
     }
 
     /// Returns the recommended delay before retrying.
@@ -69,13 +134,14 @@ impl RateLimitInfo {
         }
 
         // Fall back to calculating from reset time
-        if let Some(reset_at) = self.reset_at {
-            if let Ok(until_reset) = reset_at.duration_since(SystemTime::now()) {
-                return Some(until_reset.min(max_wait));
-            }
-        }
+        self.reset_delay(max_wait)
+    }
 
-        None
+    /// Returns the delay until `reset_at`, capped by `max_wait`, ignoring
+    /// `retry_after`.
+    pub(crate) fn reset_delay(&self, max_wait: Duration) -> Option<Duration> {
+        let until_reset = self.reset_at?.duration_since(SystemTime::now()).ok()?;
+        Some(until_reset.min(max_wait))
     }
 
     /// Returns `true` if this represents an active rate limit.
@@ -86,10 +152,211 @@ impl RateLimitInfo {
     pub fn is_rate_limited(&self) -> bool {
         self.retry_after.is_some() || self.remaining == Some(0)
     }
+
+    /// Fills in missing fields from the given limit, remaining and reset headers.
+    fn merge_headers(&mut self, headers: &HeaderMap, names: &CustomRateLimitHeaders) {
+        let value = |name: &Option<String>| header(headers, name.as_deref()?);
+
+        self.limit = self.limit.or_else(|| value(&names.limit)?.parse().ok());
+        self.remaining = self
+            .remaining
+            .or_else(|| value(&names.remaining)?.parse().ok());
+        self.reset_at = self
+            .reset_at
+            .or_else(|| names.reset_format.parse(value(&names.reset)?));
+    }
+
+    /// Fills in missing fields from the IETF draft `RateLimit` and
+    /// `RateLimit-Policy` structured fields, or the older separate
+    /// `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` fields.
+    fn merge_ietf(&mut self, headers: &HeaderMap, reset_format: ResetFormat) {
+        if let Some(value) = header(headers, "ratelimit") {
+            let (limit, remaining, reset) = parse_ietf_ratelimit(value);
+            self.limit = self.limit.or(limit);
+            self.remaining = self.remaining.or(remaining);
+            self.reset_at = self
+                .reset_at
+                .or_else(|| SystemTime::now().checked_add(reset?));
+        }
+
+        if let Some(value) = header(headers, "ratelimit-policy") {
+            let (limit, window) = parse_ietf_policy(value);
+            self.limit = self.limit.or(limit);
+            self.window = self.window.or(window);
+        }
+
+        self.merge_headers(
+            headers,
+            &CustomRateLimitHeaders::prefixed("ratelimit", reset_format),
+        );
+    }
+
+    /// Fills in missing fields from Shopify's `X-Shopify-Shop-Api-Call-Limit: {used}/{limit}`.
+    fn merge_shopify(&mut self, headers: &HeaderMap) {
+        let Some((used, limit)) = header(headers, "x-shopify-shop-api-call-limit")
+            .and_then(|value| value.split_once('/'))
+            .and_then(|(used, limit)| {
+                Some((
+                    used.trim().parse::<u64>().ok()?,
+                    limit.trim().parse::<u64>().ok()?,
+                ))
+            })
+        else {
+            return;
+        };
+
+        self.limit = self.limit.or(Some(limit));
+        self.remaining = self.remaining.or(Some(limit.saturating_sub(used)));
+    }
+}
+
+/// The rate limit headers an API sends.
+///
+/// `Retry-After` is understood by every dialect except [`Custom`](Self::Custom)
+/// dialects that name a different header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RateLimitDialect {
+    /// Try every known header, detecting whether reset values are timestamps or
+    /// delays. This is the default.
+    #[default]
+    Auto,
+
+    /// The IETF draft headers: the `RateLimit` and `RateLimit-Policy` structured
+    /// fields (e.g. `"default";r=50;t=30`), or the older `RateLimit-Limit`,
+    /// `RateLimit-Remaining` and `RateLimit-Reset` fields. Resets are delays in seconds.
+    Ietf,
+
+    /// GitHub's `X-RateLimit-Limit`, `X-RateLimit-Remaining` and
+    /// `X-RateLimit-Reset` (Unix timestamp in seconds).
+    GitHub,
+
+    /// Twitter's `X-Rate-Limit-Limit`, `X-Rate-Limit-Remaining` and
+    /// `X-Rate-Limit-Reset` (Unix timestamp in seconds).
+    Twitter,
+
+    /// Stripe, which only sends `Retry-After` on rate limited responses.
+    Stripe,
+
+    /// Shopify's `X-Shopify-Shop-Api-Call-Limit` (e.g. `32/40`) and fractional
+    /// `Retry-After`.
+    Shopify,
+
+    /// Custom header names.
+    Custom(CustomRateLimitHeaders),
+}
+
+/// Header names for [`RateLimitDialect::Custom`].
+///
+/// # Examples
+///
+/// ```
+/// use calleen::rate_limit::{CustomRateLimitHeaders, RateLimitDialect, ResetFormat};
+///
+/// let dialect = RateLimitDialect::Custom(CustomRateLimitHeaders {
+///     remaining: Some("x-quota-remaining".to_string()),
+///     reset: Some("x-quota-reset-ms".to_string()),
+///     reset_format: ResetFormat::DeltaMillis,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomRateLimitHeaders {
+    /// The header with the number of requests allowed in each window.
+    pub limit: Option<String>,
+
+    /// The header with the number of requests remaining in the current window.
+    pub remaining: Option<String>,
+
+    /// The header with the time the rate limit resets.
+    pub reset: Option<String>,
+
+    /// The format of the reset header. Defaults to [`ResetFormat::Auto`].
+    pub reset_format: ResetFormat,
+
+    /// The header with how long to wait before retrying, in seconds or as an
+    /// HTTP date. Defaults to `Retry-After` when `None`.
+    pub retry_after: Option<String>,
+}
+
+impl CustomRateLimitHeaders {
+    /// Returns the `{prefix}-limit`, `{prefix}-remaining` and `{prefix}-reset`
+    /// headers, with resets in the given format.
+    fn prefixed(prefix: &str, reset_format: ResetFormat) -> Self {
+        Self {
+            limit: Some(format!("{}-limit", prefix)),
+            remaining: Some(format!("{}-remaining", prefix)),
+            reset: Some(format!("{}-reset", prefix)),
+            reset_format,
+            ..Self::default()
+        }
+    }
+
+    fn x_ratelimit(reset_format: ResetFormat) -> Self {
+        Self::prefixed("x-ratelimit", reset_format)
+    }
+
+    fn x_rate_limit(reset_format: ResetFormat) -> Self {
+        Self::prefixed("x-rate-limit", reset_format)
+    }
+}
+
+/// The format of a rate limit reset value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResetFormat {
+    /// Detect the format from the value.
+    ///
+    /// Numbers of at least 10^12 are Unix timestamps in milliseconds, numbers of
+    /// at least 10^9 are Unix timestamps in seconds, and smaller numbers are
+    /// delays in (possibly fractional) seconds. HTTP dates are also accepted.
+    #[default]
+    Auto,
+    /// A Unix timestamp in seconds.
+    UnixSeconds,
+    /// A Unix timestamp in milliseconds.
+    UnixMillis,
+    /// A delay in (possibly fractional) seconds.
+    DeltaSeconds,
+    /// A delay in milliseconds.
+    DeltaMillis,
+}
+
+impl ResetFormat {
+    /// Parses a reset value into the time the rate limit resets.
+    fn parse(self, value: &str) -> Option<SystemTime> {
+        let Ok(number) = value.parse::<f64>() else {
+            return match self {
+                Self::Auto => httpdate::parse_http_date(value).ok(),
+                _ => None,
+            };
+        };
+        if !number.is_finite() || number < 0.0 {
+            return None;
+        }
+
+        let format = match self {
+            Self::Auto if number >= 1e12 => Self::UnixMillis,
+            Self::Auto if number >= 1e9 => Self::UnixSeconds,
+            Self::Auto => Self::DeltaSeconds,
+            format => format,
+        };
+        // Values too large to represent are treated as unparseable
+        let (base, seconds) = match format {
+            Self::UnixSeconds => (UNIX_EPOCH, number),
+            Self::UnixMillis => (UNIX_EPOCH, number / 1000.0),
+            Self::DeltaSeconds => (SystemTime::now(), number),
+            Self::DeltaMillis => (SystemTime::now(), number / 1000.0),
+            Self::Auto => unreachable!("auto format is resolved above"),
+        };
+        base.checked_add(Duration::try_from_secs_f64(seconds).ok()?)
+    }
 }
 
 /// Configuration for rate limit handling.
 ///
+/// Waiting for a rate limit to reset replaces the retry strategy's delay for
+/// that attempt, so rate limited attempts count against the strategy's
+/// `max_retries` like any other failed attempt.
+///
 /// # Examples
 ///
 /// ```
@@ -112,25 +379,138 @@ pub struct RateLimitConfig {
     /// Maximum time to wait for a rate limit reset.
     ///
     /// This prevents waiting indefinitely for rate limits. Defaults to 5 minutes.
+    /// See [`max_wait_policy`](Self::max_wait_policy) for what happens when the
+    /// server asks for a longer wait.
     pub max_wait: Duration,
 
+    /// What to do when the server asks for a wait longer than `max_wait`.
+    ///
+    /// Defaults to [`MaxWaitPolicy::Cap`].
+    pub max_wait_policy: MaxWaitPolicy,
+
     /// Whether to respect the Retry-After header.
     ///
-    /// Defaults to `true`.
+    /// When `false`, `Retry-After` is ignored when deciding how long to wait,
+    /// and only the reset time of the other rate limit headers is used. It's
+    /// still parsed into [`RateLimitInfo::retry_after`]. Defaults to `true`.
     pub respect_retry_after: bool,
+
+    /// Which requests pause together once one of them is rate limited.
+    ///
+    /// Defaults to [`RateLimitScope::Host`].
+    pub scope: RateLimitScope,
+
+    /// The rate limit headers the API sends.
+    ///
+    /// Defaults to [`RateLimitDialect::Auto`].
+    pub dialect: RateLimitDialect,
+}
+
+/// What to do when the server asks for a wait longer than
+/// [`RateLimitConfig::max_wait`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaxWaitPolicy {
+    /// Wait for `max_wait`, then retry, even though the rate limit may not have
+    /// reset yet. This is the default.
+    #[default]
+    Cap,
+
+    /// Fail immediately with [`Error::RateLimited`], without retrying.
+    ///
+    /// Requests sharing the rate limit fail the same way until it resets.
+    Fail,
+
+    /// Wait as long as the server asks for.
+    Wait,
+}
+
+/// Which requests share rate limit state.
+///
+/// Once a request is rate limited, every new or retrying request in the same
+/// bucket waits until the rate limit resets, instead of collecting rate limited
+/// responses of its own. Individual requests can be put in a named bucket with
+/// [`RequestMetadata::with_rate_limit_bucket`](crate::metadata::RequestMetadata::with_rate_limit_bucket),
+/// regardless of the scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitScope {
+    /// Each request handles its own rate limits.
+    Request,
+
+    /// Requests to the same host share a bucket, keyed by the host and, if the
+    /// base URL has an explicit port, the port (e.g. `api.example.com` or
+    /// `localhost:8080`).
+    #[default]
+    Host,
+
+    /// All requests made by the client share a single bucket, keyed
+    /// [`CLIENT_BUCKET`].
+    Client,
 }
 
+/// The bucket key used by [`RateLimitScope::Client`].
+pub const CLIENT_BUCKET: &str = "client";
+
 impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
             enabled: true,
             max_wait: Duration::from_secs(300), // 5 minutes
+            max_wait_policy: MaxWaitPolicy::default(),
             respect_retry_after: true,
+            scope: RateLimitScope::default(),
+            dialect: RateLimitDialect::default(),
         }
     }
 }
 
 impl RateLimitConfig {
+    /// Returns how long to wait before retrying after `error`, with the max wait
+    /// policy applied, or `None` if the error isn't rate limited.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::RateLimited`] if the wait exceeds `max_wait` and the
+    /// policy is [`MaxWaitPolicy::Fail`].
+    pub(crate) fn delay_for(&self, error: &Error) -> Result<Option<Duration>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let Some(info) = error.rate_limit_info() else {
+            return Ok(None);
+        };
+        let delay = if self.respect_retry_after {
+            info.delay(Duration::MAX)
+        } else {
+            info.reset_delay(Duration::MAX)
+        };
+        let Some(delay) = delay else {
+            return Ok(None);
+        };
+        match self.apply_max_wait(delay) {
+            Ok(delay) => Ok(Some(delay)),
+            Err(Error::RateLimited { retry_after, .. }) => Err(Error::RateLimited {
+                retry_after,
+                request: error.request().cloned().map(Box::new),
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Applies the max wait policy to a rate limit wait.
+    pub(crate) fn apply_max_wait(&self, delay: Duration) -> Result<Duration> {
+        if delay <= self.max_wait {
+            return Ok(delay);
+        }
+        match self.max_wait_policy {
+            MaxWaitPolicy::Cap => Ok(self.max_wait),
+            MaxWaitPolicy::Fail => Err(Error::RateLimited {
+                retry_after: delay,
+                request: None,
+            }),
+            MaxWaitPolicy::Wait => Ok(delay),
+        }
+    }
+
     /// Creates a new builder for configuring rate limit handling.
     pub fn builder() -> RateLimitConfigBuilder {
         RateLimitConfigBuilder::default()
@@ -150,7 +530,10 @@ impl RateLimitConfig {
 pub struct RateLimitConfigBuilder {
     enabled: Option<bool>,
     max_wait: Option<Duration>,
+    max_wait_policy: Option<MaxWaitPolicy>,
     respect_retry_after: Option<bool>,
+    scope: Option<RateLimitScope>,
+    dialect: Option<RateLimitDialect>,
 }
 
 impl RateLimitConfigBuilder {
@@ -166,73 +549,264 @@ impl RateLimitConfigBuilder {
         self
     }
 
+    /// Sets what to do when the server asks for a wait longer than the maximum.
+    pub fn max_wait_policy(mut self, policy: MaxWaitPolicy) -> Self {
+        self.max_wait_policy = Some(policy);
+        self
+    }
+
     /// Sets whether to respect the Retry-After header.
     pub fn respect_retry_after(mut self, respect: bool) -> Self {
         self.respect_retry_after = Some(respect);
         self
     }
 
+    /// Sets which requests pause together once one of them is rate limited.
+    pub fn scope(mut self, scope: RateLimitScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Sets the rate limit headers the API sends.
+    pub fn dialect(mut self, dialect: RateLimitDialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
     /// Builds the `RateLimitConfig`.
     pub fn build(self) -> RateLimitConfig {
         let default = RateLimitConfig::default();
         RateLimitConfig {
             enabled: self.enabled.unwrap_or(default.enabled),
             max_wait: self.max_wait.unwrap_or(default.max_wait),
+            max_wait_policy: self.max_wait_policy.unwrap_or(default.max_wait_policy),
             respect_retry_after: self
                 .respect_retry_after
                 .unwrap_or(default.respect_retry_after),
+            scope: self.scope.unwrap_or(default.scope),
+            dialect: self.dialect.unwrap_or(default.dialect),
+        }
+    }
+}
+
+/// How long a gate blocks for when a delay is too long to represent.
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+/// Pauses every request sharing it once one of them is rate limited.
+///
+/// Without a shared gate, concurrent requests that hit a rate limit would each
+/// wait and retry on their own schedule, and requests that haven't been rate
+/// limited yet would keep hitting the server.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitGate {
+    blocked_until: Mutex<Option<Instant>>,
+}
+
+impl RateLimitGate {
+    /// Blocks requests for `delay`, unless they're already blocked for longer.
+    ///
+    /// Delays too long to represent block requests until the far future.
+    pub(crate) fn block_for(&self, delay: Duration) {
+        let now = Instant::now();
+        let until = now.checked_add(delay).unwrap_or_else(|| now + FAR_FUTURE);
+        let mut blocked_until = self.blocked_until.lock().unwrap_or_else(|e| e.into_inner());
+        if blocked_until.is_none_or(|current| current < until) {
+            *blocked_until = Some(until);
         }
     }
+
+    /// Returns when requests will be unblocked, or `None` if they aren't blocked.
+    pub(crate) fn blocked_until(&self) -> Option<Instant> {
+        self.blocked_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .filter(|until| *until > Instant::now())
+    }
+
+    /// Returns how long requests will remain blocked, or `None` if they aren't blocked.
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        Some(self.blocked_until()? - Instant::now())
+    }
+
+    /// Waits until requests are no longer blocked.
+    ///
+    /// Returns how long was waited, or `None` if requests weren't blocked.
+    pub(crate) async fn wait(&self) -> Option<Duration> {
+        let start = Instant::now();
+        let mut waited = false;
+        loop {
+            // The deadline may be extended while waiting, so check it again afterwards
+            match self.blocked_until() {
+                Some(until) => {
+                    tokio::time::sleep_until(until).await;
+                    waited = true;
+                }
+                None => return waited.then(|| start.elapsed()),
+            }
+        }
+    }
+}
+
+/// The rate limit gates of a client, one per bucket.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitGates {
+    gates: Mutex<HashMap<String, Arc<RateLimitGate>>>,
+}
+
+impl RateLimitGates {
+    /// Returns the gate for the bucket a request belongs to, if any.
+    pub(crate) fn for_request(
+        &self,
+        config: &RateLimitConfig,
+        metadata: &RequestMetadata,
+        url: &Url,
+    ) -> Option<Arc<RateLimitGate>> {
+        let key = match (&metadata.rate_limit_bucket, config.scope) {
+            (Some(bucket), _) => bucket.clone(),
+            (None, RateLimitScope::Request) => return None,
+            (None, RateLimitScope::Host) => match url.port() {
+                Some(port) => format!("{}:{}", url.host_str()?, port),
+                None => url.host_str()?.to_string(),
+            },
+            (None, RateLimitScope::Client) => CLIENT_BUCKET.to_string(),
+        };
+
+        let mut gates = self.gates.lock().unwrap_or_else(|e| e.into_inner());
+        if !gates.contains_key(&key) {
+            // Forget gates that are no longer blocked or in use, so per-request
+            // buckets don't accumulate
+            gates.retain(|_, gate| Arc::strong_count(gate) > 1 || gate.blocked_until().is_some());
+        }
+        Some(gates.entry(key).or_default().clone())
+    }
+
+    /// Returns when the given bucket will be unblocked, or `None` if it isn't blocked.
+    pub(crate) fn blocked_until(&self, bucket: &str) -> Option<Instant> {
+        self.gates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(bucket)?
+            .blocked_until()
+    }
+
+    /// Returns every currently blocked bucket, with when it will be unblocked.
+    pub(crate) fn blocked(&self) -> HashMap<String, Instant> {
+        self.gates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter_map(|(key, gate)| Some((key.clone(), gate.blocked_until()?)))
+            .collect()
+    }
 }
 
 /// Parses the Retry-After header.
 ///
-/// Supports both delay-seconds (integer) and HTTP-date formats.
+/// Supports both delay-seconds (integer or fractional) and HTTP-date formats.
 fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
-    let header = headers.get("retry-after")?.to_str().ok()?;
+    parse_delay(header(headers, "retry-after")?)
+}
 
-    // Try parsing as seconds (integer)
-    if let Ok(seconds) = header.parse::<u64>() {
-        return Some(Duration::from_secs(seconds));
+/// Parses a delay in seconds or an HTTP date into a duration from now.
+fn parse_delay(value: &str) -> Option<Duration> {
+    // Try parsing as seconds, which some APIs send with a fractional part.
+    // Negative values and values too large to represent are rejected.
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
     }
 
     // Try parsing as HTTP date (RFC 7231 format)
-    if let Ok(date_time) = httpdate::parse_http_date(header) {
-        if let Ok(duration) = date_time.duration_since(SystemTime::now()) {
-            return Some(duration);
-        }
-    }
+    let date_time = httpdate::parse_http_date(value).ok()?;
+    date_time.duration_since(SystemTime::now()).ok()
+}
+
+/// Returns the trimmed value of a header, if it's present and valid UTF-8.
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    Some(headers.get(name)?.to_str().ok()?.trim())
+}
+
+/// Splits a structured field list or dictionary into its members.
+fn members(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|member| !member.is_empty())
+}
 
-    None
+/// Splits a structured field member into its value and `key=value` parameters.
+fn parameters(member: &str) -> (&str, impl Iterator<Item = (&str, &str)>) {
+    let mut parts = member.split(';').map(str::trim);
+    let value = parts.next().unwrap_or_default();
+    let params = parts.filter_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        Some((key.trim(), value.trim().trim_matches('"')))
+    });
+    (value, params)
 }
 
-/// Parses X-RateLimit-Reset or RateLimit-Reset headers (Unix timestamp).
-fn parse_rate_limit_reset(headers: &HeaderMap) -> Option<SystemTime> {
-    // Try X-RateLimit-Reset first (more common)
-    if let Some(header) = headers.get("x-ratelimit-reset") {
-        if let Ok(timestamp_str) = header.to_str() {
-            if let Ok(timestamp) = timestamp_str.parse::<u64>() {
-                return Some(UNIX_EPOCH + Duration::from_secs(timestamp));
+/// Parses the limit, remaining requests and reset delay from an IETF `RateLimit`
+/// field.
+///
+/// Supports the list form of recent drafts (`"default";r=50;t=30`), using the
+/// policy with the fewest remaining requests, and the dictionary form of earlier
+/// drafts (`limit=100, remaining=50, reset=30`).
+fn parse_ietf_ratelimit(value: &str) -> (Option<u64>, Option<u64>, Option<Duration>) {
+    let mut dictionary = (None, None, None);
+    let mut most_limited: Option<(u64, Option<Duration>)> = None;
+
+    for member in members(value) {
+        let (item, params) = parameters(member);
+        if let Some((key, value)) = item.split_once('=') {
+            match key.trim() {
+                "limit" => dictionary.0 = value.trim().parse().ok(),
+                "remaining" => dictionary.1 = value.trim().parse().ok(),
+                "reset" => dictionary.2 = parse_delay(value.trim()),
+                _ => {}
             }
+            continue;
         }
-    }
 
-    // Try RateLimit-Reset (draft standard)
-    if let Some(header) = headers.get("ratelimit-reset") {
-        if let Ok(timestamp_str) = header.to_str() {
-            if let Ok(timestamp) = timestamp_str.parse::<u64>() {
-                return Some(UNIX_EPOCH + Duration::from_secs(timestamp));
+        let (mut remaining, mut reset) = (None, None);
+        for (key, value) in params {
+            match key {
+                "r" => remaining = value.parse::<u64>().ok(),
+                "t" => reset = parse_delay(value),
+                _ => {}
+            }
+        }
+        if let Some(remaining) = remaining {
+            if most_limited.is_none_or(|(fewest, _)| remaining < fewest) {
+                most_limited = Some((remaining, reset));
             }
         }
     }
 
-    None
+    match most_limited {
+        Some((remaining, reset)) => (dictionary.0, Some(remaining), reset.or(dictionary.2)),
+        None => dictionary,
+    }
 }
 
-/// Parses X-RateLimit-Remaining header.
-fn parse_rate_limit_remaining(headers: &HeaderMap) -> Option<u64> {
-    let header = headers.get("x-ratelimit-remaining")?.to_str().ok()?;
-    header.parse().ok()
+/// Parses the limit and window from an IETF `RateLimit-Policy` field, using the
+/// first policy.
+///
+/// Supports both `"default";q=100;w=60` and the earlier `100;w=60`.
+fn parse_ietf_policy(value: &str) -> (Option<u64>, Option<Duration>) {
+    let Some(member) = members(value).next() else {
+        return (None, None);
+    };
+
+    let (item, params) = parameters(member);
+    let mut limit = item.parse().ok();
+    let mut window = None;
+    for (key, value) in params {
+        match key {
+            "q" => limit = value.parse().ok(),
+            "w" => window = value.parse().ok().map(Duration::from_secs),
+            _ => {}
+        }
+    }
+    (limit, window)
 }
 
 #[cfg(test)]
@@ -298,7 +872,7 @@ mod tests {
             HeaderValue::from_str(&future_timestamp.to_string()).unwrap(),
         );
 
-        let reset_at = parse_rate_limit_reset(&headers);
+        let reset_at = RateLimitInfo::from_headers(&headers).reset_at;
         assert!(reset_at.is_some());
     }
 
@@ -307,7 +881,7 @@ mod tests {
         let mut headers = HeaderMap::new();
         headers.insert("x-ratelimit-remaining", HeaderValue::from_static("42"));
 
-        let remaining = parse_rate_limit_remaining(&headers);
+        let remaining = RateLimitInfo::from_headers(&headers).remaining;
         assert_eq!(remaining, Some(42));
     }
 
@@ -326,12 +900,241 @@ mod tests {
     #[test]
     fn test_rate_limit_delay_capped_by_max_wait() {
         let info = RateLimitInfo {
-            reset_at: None,
             retry_after: Some(Duration::from_secs(600)),
             remaining: Some(0),
+            ..RateLimitInfo::default()
         };
 
         let delay = info.delay(Duration::from_secs(300));
         assert_eq!(delay, Some(Duration::from_secs(300)));
     }
+
+    #[test]
+    fn test_block_for_saturates_huge_delays() {
+        let gate = RateLimitGate::default();
+        gate.block_for(Duration::MAX);
+        let until = gate.blocked_until().unwrap();
+        assert!(until > Instant::now() + Duration::from_secs(86400 * 365));
+
+        // A shorter delay doesn't shorten the block
+        gate.block_for(Duration::from_secs(1));
+        assert_eq!(gate.blocked_until(), Some(until));
+    }
+
+    #[test]
+    fn test_rate_limit_bucket_keys() {
+        let gates = RateLimitGates::default();
+        let metadata = RequestMetadata::new(http::Method::GET, "/test");
+        let url = Url::parse("https://api.example.com/test").unwrap();
+        let local_url = Url::parse("http://localhost:8080/test").unwrap();
+
+        let config = RateLimitConfig::default();
+        gates
+            .for_request(&config, &metadata, &url)
+            .unwrap()
+            .block_for(Duration::from_secs(60));
+        gates
+            .for_request(&config, &metadata, &local_url)
+            .unwrap()
+            .block_for(Duration::from_secs(60));
+        assert!(gates.blocked_until("api.example.com").is_some());
+        assert!(gates.blocked_until("localhost:8080").is_some());
+
+        let config = RateLimitConfig::builder()
+            .scope(RateLimitScope::Request)
+            .build();
+        assert!(gates.for_request(&config, &metadata, &url).is_none());
+
+        let metadata = metadata.with_rate_limit_bucket("search");
+        gates
+            .for_request(&config, &metadata, &url)
+            .unwrap()
+            .block_for(Duration::from_secs(60));
+        assert_eq!(gates.blocked().len(), 3);
+    }
+
+    #[test]
+    fn test_rate_limit_gates_evict_unblocked_buckets() {
+        let gates = RateLimitGates::default();
+        let config = RateLimitConfig::default();
+        let url = Url::parse("https://api.example.com/test").unwrap();
+
+        let blocked = RequestMetadata::new(http::Method::GET, "/a").with_rate_limit_bucket("a");
+        gates
+            .for_request(&config, &blocked, &url)
+            .unwrap()
+            .block_for(Duration::from_secs(60));
+        let in_use = RequestMetadata::new(http::Method::GET, "/b").with_rate_limit_bucket("b");
+        let in_use_gate = gates.for_request(&config, &in_use, &url).unwrap();
+        for bucket in ["c", "d", "e"] {
+            let metadata =
+                RequestMetadata::new(http::Method::GET, "/").with_rate_limit_bucket(bucket);
+            gates.for_request(&config, &metadata, &url);
+        }
+
+        let buckets = gates.gates.lock().unwrap();
+        let mut keys: Vec<&str> = buckets.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["a", "b", "e"]);
+        drop(in_use_gate);
+    }
+
+    #[test]
+    fn test_reset_format_detection() {
+        let now = SystemTime::now();
+        let unix_seconds = now.duration_since(UNIX_EPOCH).unwrap().as_secs() + 60;
+
+        let reset = ResetFormat::Auto.parse(&unix_seconds.to_string()).unwrap();
+        assert_eq!(reset, UNIX_EPOCH + Duration::from_secs(unix_seconds));
+
+        let reset = ResetFormat::Auto
+            .parse(&(unix_seconds * 1000).to_string())
+            .unwrap();
+        assert_eq!(reset, UNIX_EPOCH + Duration::from_secs(unix_seconds));
+
+        let reset = ResetFormat::Auto.parse("1.5").unwrap();
+        let delay = reset.duration_since(now).unwrap();
+        assert!(delay >= Duration::from_millis(1500) && delay < Duration::from_secs(2));
+
+        let reset = ResetFormat::DeltaMillis.parse("2500").unwrap();
+        let delay = reset.duration_since(now).unwrap();
+        assert!(delay >= Duration::from_millis(2500) && delay < Duration::from_secs(3));
+
+        assert!(ResetFormat::Auto.parse("soon").is_none());
+        assert!(ResetFormat::Auto.parse("-1").is_none());
+    }
+
+    #[test]
+    fn test_ietf_structured_fields() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "ratelimit",
+            HeaderValue::from_static(r#""hourly";r=100;t=3000, "burst";r=0;t=5"#),
+        );
+        headers.insert(
+            "ratelimit-policy",
+            HeaderValue::from_static(r#""hourly";q=1000;w=3600, "burst";q=10;w=10"#),
+        );
+
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Ietf);
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.limit, Some(1000));
+        assert_eq!(info.window, Some(Duration::from_secs(3600)));
+        let delay = info.delay(Duration::from_secs(300)).unwrap();
+        assert!(delay > Duration::from_secs(4) && delay <= Duration::from_secs(5));
+        assert!(info.is_rate_limited());
+    }
+
+    #[test]
+    fn test_ietf_earlier_drafts() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "ratelimit",
+            HeaderValue::from_static("limit=100, remaining=0, reset=10"),
+        );
+        headers.insert("ratelimit-policy", HeaderValue::from_static("100;w=60"));
+
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Ietf);
+        assert_eq!(info.limit, Some(100));
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.window, Some(Duration::from_secs(60)));
+
+        // Separate fields, where the reset is always a delay
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-limit", HeaderValue::from_static("100"));
+        headers.insert("ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("ratelimit-reset", HeaderValue::from_static("30"));
+
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Ietf);
+        assert_eq!(info.limit, Some(100));
+        let delay = info.delay(Duration::from_secs(300)).unwrap();
+        assert!(delay > Duration::from_secs(29) && delay <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_vendor_dialects() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-rate-limit-limit", HeaderValue::from_static("900"));
+        headers.insert("x-rate-limit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-rate-limit-reset", HeaderValue::from_static("1700000000"));
+
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Twitter);
+        assert_eq!(info.limit, Some(900));
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(
+            info.reset_at,
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+        // GitHub uses different header names
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::GitHub);
+        assert_eq!(info.remaining, None);
+
+        // A reset too small for Auto to detect as a timestamp is still one
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("500000000"));
+        let github = RateLimitInfo::parse(&headers, &RateLimitDialect::GitHub);
+        assert_eq!(
+            github.reset_at,
+            Some(UNIX_EPOCH + Duration::from_secs(500_000_000))
+        );
+        assert_eq!(github.delay(Duration::MAX), None);
+        let auto = RateLimitInfo::parse(&headers, &RateLimitDialect::Auto);
+        assert!(auto.reset_at > Some(SystemTime::now()));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-shopify-shop-api-call-limit",
+            HeaderValue::from_static("40/40"),
+        );
+        headers.insert("retry-after", HeaderValue::from_static("2.0"));
+
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Shopify);
+        assert_eq!(info.limit, Some(40));
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.retry_after, Some(Duration::from_secs(2)));
+
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Stripe);
+        assert_eq!(info.remaining, None);
+        assert_eq!(info.retry_after, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_ignored() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("1e20"));
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert("retry-after", HeaderValue::from_static("-5"));
+        assert_eq!(parse_retry_after(&headers), None);
+
+        assert_eq!(ResetFormat::DeltaSeconds.parse("1e19"), None);
+        assert_eq!(ResetFormat::Auto.parse("1e30"), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "ratelimit",
+            HeaderValue::from_static("limit=100, remaining=0, reset=1e19"),
+        );
+        let info = RateLimitInfo::parse(&headers, &RateLimitDialect::Ietf);
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.reset_at, None);
+    }
+
+    #[test]
+    fn test_custom_dialect() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-quota-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-quota-wait", HeaderValue::from_static("7"));
+        headers.insert("retry-after", HeaderValue::from_static("60"));
+
+        let dialect = RateLimitDialect::Custom(CustomRateLimitHeaders {
+            remaining: Some("x-quota-remaining".to_string()),
+            retry_after: Some("x-quota-wait".to_string()),
+            ..Default::default()
+        });
+        let info = RateLimitInfo::parse(&headers, &dialect);
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.retry_after, Some(Duration::from_secs(7)));
+    }
 }
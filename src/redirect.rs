@@ -0,0 +1,280 @@
+//! Redirect handling.
+//!
+//! The client follows redirects itself, with any transport, according to the
+//! [`RedirectPolicy`] set with [`ClientBuilder::redirect_policy`](crate::ClientBuilder::redirect_policy).
+//! Every request in a redirect chain gets the client's cookies for its own URL,
+//! and counts towards the same attempt's timeout. Requests are signed with the
+//! client's [`RequestSigner`](crate::signing::RequestSigner) until credentials
+//! are stripped from the chain, since a signature usually doesn't cover the host
+//! and would be valid for the original service if replayed.
+//!
+//! The URL a response finally came from and the URLs that redirected to it are
+//! recorded in [`Response::final_url`](crate::Response::final_url) and
+//! [`Response::redirects`](crate::Response::redirects), and in the
+//! [`RequestSummary`](crate::RequestSummary) of a response or error.
+//!
+//! A redirect the policy doesn't allow isn't followed. Instead, the redirect
+//! response itself is returned, as an [`Error::HttpError`](crate::Error::HttpError)
+//! with its 3xx status.
+
+use http::header::{HeaderMap, AUTHORIZATION, COOKIE, LOCATION, PROXY_AUTHORIZATION};
+use http::StatusCode;
+use url::Url;
+
+/// What to do with a redirect to a different origin (scheme, host or port).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrossOriginRedirects {
+    /// Follow it with the same headers.
+    ///
+    /// Only use this when every origin the service redirects to is trusted with
+    /// its credentials.
+    Follow,
+
+    /// Follow it without the `Authorization`, `Proxy-Authorization` and `Cookie`
+    /// headers, and without signing it. Cookies from a
+    /// [`CookieJar`](crate::cookie::CookieJar) are still sent to the origins they
+    /// belong to.
+    ///
+    /// Once credentials are stripped, the rest of the redirect chain isn't signed
+    /// either, even if it leads back to the original origin.
+    #[default]
+    StripCredentials,
+
+    /// Don't follow it.
+    Refuse,
+}
+
+/// Configures which redirects are followed.
+///
+/// By default, up to 10 redirects are followed per request, and credentials
+/// are dropped when redirected to another origin.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::redirect::{CrossOriginRedirects, RedirectPolicy};
+///
+/// let policy = RedirectPolicy::builder()
+///     .max_redirects(3)
+///     .cross_origin(CrossOriginRedirects::Refuse)
+///     .build();
+///
+/// // Return every redirect to the caller
+/// let none = RedirectPolicy::none();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedirectPolicy {
+    /// The maximum number of redirects followed per request. `0` disables redirects.
+    pub max_redirects: usize,
+
+    /// What to do with redirects to a different origin.
+    pub cross_origin: CrossOriginRedirects,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self {
+            max_redirects: 10,
+            cross_origin: CrossOriginRedirects::StripCredentials,
+        }
+    }
+}
+
+impl RedirectPolicy {
+    /// Creates a new builder for configuring redirects.
+    pub fn builder() -> RedirectPolicyBuilder {
+        RedirectPolicyBuilder {
+            policy: Self::default(),
+        }
+    }
+
+    /// A policy that doesn't follow any redirects.
+    pub fn none() -> Self {
+        Self {
+            max_redirects: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the URL to follow a redirect response from `url` to, or `None`
+    /// if it isn't a redirect or shouldn't be followed.
+    ///
+    /// `followed` is the number of redirects followed so far.
+    pub(crate) fn next_url(
+        &self,
+        url: &Url,
+        status: StatusCode,
+        headers: &HeaderMap,
+        followed: usize,
+    ) -> Option<Url> {
+        if !matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308) {
+            return None;
+        }
+        let location = headers.get(LOCATION)?.to_str().ok()?;
+        let Ok(next) = url.join(location) else {
+            tracing::warn!(
+                location = location,
+                "Not following redirect to an invalid URL"
+            );
+            return None;
+        };
+        if !matches!(next.scheme(), "http" | "https") {
+            tracing::warn!(location = %next, "Not following redirect to a non-HTTP URL");
+            return None;
+        }
+
+        if followed >= self.max_redirects {
+            tracing::warn!(
+                location = %next,
+                max_redirects = self.max_redirects,
+                "Not following redirect: too many redirects"
+            );
+            return None;
+        }
+        if self.cross_origin == CrossOriginRedirects::Refuse && !same_origin(url, &next) {
+            tracing::warn!(
+                from = %url,
+                location = %next,
+                "Not following redirect to another origin"
+            );
+            return None;
+        }
+        Some(next)
+    }
+
+    /// Removes credentials from the headers of a request redirected from `from`
+    /// to `to`, if the policy requires it.
+    ///
+    /// Returns `true` if credentials were stripped, in which case the request
+    /// mustn't be signed either.
+    pub(crate) fn strip_credentials(&self, from: &Url, to: &Url, headers: &mut HeaderMap) -> bool {
+        if self.cross_origin != CrossOriginRedirects::StripCredentials || same_origin(from, to) {
+            return false;
+        }
+        for name in [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE] {
+            headers.remove(name);
+        }
+        true
+    }
+}
+
+/// Builder for `RedirectPolicy`.
+pub struct RedirectPolicyBuilder {
+    policy: RedirectPolicy,
+}
+
+impl RedirectPolicyBuilder {
+    /// Sets the maximum number of redirects followed per request.
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.policy.max_redirects = max;
+        self
+    }
+
+    /// Sets what to do with redirects to a different origin.
+    pub fn cross_origin(mut self, cross_origin: CrossOriginRedirects) -> Self {
+        self.policy.cross_origin = cross_origin;
+        self
+    }
+
+    /// Builds the `RedirectPolicy`.
+    pub fn build(self) -> RedirectPolicy {
+        self.policy
+    }
+}
+
+/// Returns whether a redirect with `status` changes a `method` request into a
+/// `GET` without a body, as browsers do.
+pub(crate) fn changes_to_get(status: StatusCode, method: &http::Method) -> bool {
+    match status.as_u16() {
+        303 => method != http::Method::HEAD,
+        301 | 302 => method == http::Method::POST,
+        _ => false,
+    }
+}
+
+fn same_origin(a: &Url, b: &Url) -> bool {
+    a.origin() == b.origin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn location(location: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LOCATION, HeaderValue::from_str(location).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_next_url() {
+        let policy = RedirectPolicy::default();
+        let url = Url::parse("https://api.example.com/v1/users").unwrap();
+        let next = |status: u16, headers: &HeaderMap, followed| {
+            policy
+                .next_url(
+                    &url,
+                    StatusCode::from_u16(status).unwrap(),
+                    headers,
+                    followed,
+                )
+                .map(|url| url.to_string())
+        };
+
+        assert_eq!(
+            next(301, &location("/v2/users"), 0).as_deref(),
+            Some("https://api.example.com/v2/users")
+        );
+        assert_eq!(
+            next(307, &location("https://other.example.com/"), 0).as_deref(),
+            Some("https://other.example.com/")
+        );
+        assert_eq!(next(200, &location("/v2/users"), 0), None);
+        assert_eq!(next(304, &location("/v2/users"), 0), None);
+        assert_eq!(next(302, &HeaderMap::new(), 0), None);
+        assert_eq!(next(302, &location("ftp://example.com/"), 0), None);
+        assert_eq!(next(302, &location("/v2/users"), 10), None);
+    }
+
+    #[test]
+    fn test_cross_origin() {
+        let from = Url::parse("https://api.example.com/").unwrap();
+        let same = Url::parse("https://api.example.com/other").unwrap();
+        let other = Url::parse("http://api.example.com/").unwrap();
+
+        let refuse = RedirectPolicy::builder()
+            .cross_origin(CrossOriginRedirects::Refuse)
+            .build();
+        let status = StatusCode::FOUND;
+        assert!(refuse
+            .next_url(&from, status, &location(same.as_str()), 0)
+            .is_some());
+        assert!(refuse
+            .next_url(&from, status, &location(other.as_str()), 0)
+            .is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer token"));
+        headers.insert("x-request-id", HeaderValue::from_static("1"));
+        let policy = RedirectPolicy::default();
+        assert!(!policy.strip_credentials(&from, &same, &mut headers));
+        assert!(headers.contains_key(AUTHORIZATION));
+        assert!(policy.strip_credentials(&from, &other, &mut headers));
+        assert!(!headers.contains_key(AUTHORIZATION));
+        assert!(headers.contains_key("x-request-id"));
+    }
+
+    #[test]
+    fn test_changes_to_get() {
+        let see_other = StatusCode::SEE_OTHER;
+        assert!(changes_to_get(see_other, &http::Method::PUT));
+        assert!(!changes_to_get(see_other, &http::Method::HEAD));
+        assert!(changes_to_get(StatusCode::FOUND, &http::Method::POST));
+        assert!(!changes_to_get(StatusCode::FOUND, &http::Method::PUT));
+        assert!(!changes_to_get(
+            StatusCode::TEMPORARY_REDIRECT,
+            &http::Method::POST
+        ));
+    }
+}
@@ -4,8 +4,9 @@
 //! about the HTTP request, making it easy to access timing information, headers,
 //! and the raw response body for debugging and observability.
 
-use http::{HeaderMap, StatusCode};
+use http::{HeaderMap, Method, StatusCode};
 use std::time::Duration;
+use url::Url;
 
 /// A wrapper around a successful HTTP response.
 ///
@@ -57,6 +58,9 @@ pub struct Response<T> {
     ///
     /// This is useful for debugging, logging, or when you need to inspect
     /// the exact response from the server.
+    ///
+    /// The body is decoded as UTF-8, with invalid sequences replaced by
+    /// `U+FFFD`. A `charset` parameter in `Content-Type` is not honored.
     pub raw_body: String,
 
     /// The HTTP status code of the response.
@@ -68,7 +72,8 @@ pub struct Response<T> {
     /// The total latency of the request, including all retry attempts.
     ///
     /// This measures the time from when the first request was sent until
-    /// the successful response was received.
+    /// the successful response was received, including retry delays. See
+    /// [`timings`](Response::timings) for the server's latency alone.
     pub latency: Duration,
 
     /// The number of attempts made to complete this request.
@@ -76,6 +81,35 @@ pub struct Response<T> {
     /// This will be `1` for requests that succeeded on the first try,
     /// and higher for requests that required retries.
     pub attempts: usize,
+
+    /// The number of hedged requests sent while completing this request.
+    ///
+    /// This is always `0` unless hedging is enabled on the client. Hedges
+    /// sent during every attempt are included.
+    pub hedges: usize,
+
+    /// The URL the response came from, after following any redirects.
+    ///
+    /// This is only `None` for responses created with [`Response::new`].
+    pub final_url: Option<Url>,
+
+    /// The URLs that redirected to [`final_url`](Response::final_url), in the
+    /// order they were requested. Empty if the request wasn't redirected.
+    pub redirects: Vec<Url>,
+
+    /// The request that produced this response.
+    ///
+    /// This is only `None` for responses created with [`Response::new`].
+    pub request: Option<RequestSummary>,
+
+    /// Where the time went in the attempt that produced this response.
+    pub timings: Timings,
+
+    /// Every attempt made to complete this request, in order, ending with the
+    /// one that produced this response.
+    ///
+    /// This is only empty for responses created with [`Response::new`].
+    pub attempt_history: Vec<Attempt>,
 }
 
 impl<T> Response<T> {
@@ -98,6 +132,12 @@ impl<T> Response<T> {
             headers,
             latency,
             attempts,
+            hedges: 0,
+            final_url: None,
+            redirects: Vec::new(),
+            request: None,
+            timings: Timings::default(),
+            attempt_history: Vec::new(),
         }
     }
 
@@ -135,6 +175,12 @@ impl<T> Response<T> {
             headers: self.headers,
             latency: self.latency,
             attempts: self.attempts,
+            hedges: self.hedges,
+            final_url: self.final_url,
+            redirects: self.redirects,
+            request: self.request,
+            timings: self.timings,
+            attempt_history: self.attempt_history,
         }
     }
 
@@ -161,6 +207,32 @@ impl<T> Response<T> {
         self.attempts > 1
     }
 
+    /// Returns the total time spent waiting before attempts, in retry backoff
+    /// and for rate limits.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use calleen::Client;
+    ///
+    /// # async fn example() -> Result<(), calleen::Error> {
+    /// let client = Client::builder()
+    ///     .base_url("https://api.example.com")?
+    ///     .build()?;
+    ///
+    /// let response = client.get::<serde_json::Value>("/users").await?;
+    /// println!("Server latency: {:?}", response.timings.server());
+    /// println!("Waited for retries: {:?}", response.waited());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn waited(&self) -> Duration {
+        self.attempt_history
+            .iter()
+            .map(|attempt| attempt.timings.waited)
+            .sum()
+    }
+
     /// Returns a reference to a header value by name.
     ///
     /// # Examples
@@ -191,6 +263,159 @@ impl<T> Response<T> {
     }
 }
 
+impl<T> Response<Option<T>> {
+    /// Converts a response with optional data into an optional response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use calleen::Response;
+    /// # use http::{HeaderMap, StatusCode};
+    /// # use std::time::Duration;
+    /// let response = Response::new(
+    ///     Some(42),
+    ///     "42".to_string(),
+    ///     StatusCode::OK,
+    ///     HeaderMap::new(),
+    ///     Duration::from_millis(100),
+    ///     1,
+    /// );
+    ///
+    /// assert_eq!(response.transpose().unwrap().data, 42);
+    /// ```
+    pub fn transpose(self) -> Option<Response<T>> {
+        let Response {
+            data,
+            raw_body,
+            status,
+            headers,
+            latency,
+            attempts,
+            hedges,
+            final_url,
+            redirects,
+            request,
+            timings,
+            attempt_history,
+        } = self;
+
+        data.map(|data| Response {
+            data,
+            raw_body,
+            status,
+            headers,
+            latency,
+            attempts,
+            hedges,
+            final_url,
+            redirects,
+            request,
+            timings,
+            attempt_history,
+        })
+    }
+}
+
+/// A breakdown of the time taken by an attempt.
+///
+/// Phases an attempt didn't reach, such as reading the body of a request that
+/// failed to connect, are zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Time spent waiting before the attempt was sent, in retry backoff and for
+    /// rate limits.
+    pub waited: Duration,
+
+    /// Time from sending the request until the response headers arrived,
+    /// including any redirects.
+    pub headers: Duration,
+
+    /// Time spent reading and decompressing the response body.
+    ///
+    /// Transports that don't report it, such as most custom transports, count
+    /// reading the body towards [`headers`](Timings::headers) instead.
+    pub body: Duration,
+
+    /// Time spent deserializing the response body.
+    pub deserialize: Duration,
+
+    /// Time from sending the request until the attempt finished, not including
+    /// [`waited`](Timings::waited).
+    pub total: Duration,
+}
+
+impl Timings {
+    /// Returns the time the server took to respond, from sending the request
+    /// until its body was read.
+    pub fn server(&self) -> Duration {
+        self.headers + self.body
+    }
+}
+
+/// The outcome of a single attempt of a request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    /// The attempt number, starting at `1`.
+    pub number: usize,
+
+    /// The status of the response, if one was received.
+    pub status: Option<StatusCode>,
+
+    /// The error the attempt failed with, if it failed.
+    ///
+    /// This is the error's message, with the client's
+    /// [`RedactionPolicy`](crate::redact::RedactionPolicy) length limit applied.
+    pub error: Option<String>,
+
+    /// Where the time went in the attempt.
+    pub timings: Timings,
+}
+
+/// What was sent for the last request of an attempt.
+///
+/// When a request is redirected, this describes the request to the final URL.
+/// Headers have the client's [`RedactionPolicy`](crate::redact::RedactionPolicy)
+/// applied, so credentials are masked.
+///
+/// # Examples
+///
+/// ```no_run
+/// use calleen::{Client, Error};
+///
+/// # async fn example() -> Result<(), Error> {
+/// let client = Client::builder()
+///     .base_url("https://api.example.com")?
+///     .build()?;
+///
+/// if let Err(e) = client.get::<serde_json::Value>("/users").await {
+///     if let Some(request) = e.request() {
+///         eprintln!("{} {} failed: {}", request.method, request.url, e);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestSummary {
+    /// The request method.
+    pub method: Method,
+
+    /// The URL requested, after following any redirects, with credentials and
+    /// query parameter values redacted.
+    pub url: Url,
+
+    /// The URLs that redirected to [`url`](RequestSummary::url), in the order
+    /// they were requested and redacted the same way. Empty if the request
+    /// wasn't redirected.
+    pub redirects: Vec<Url>,
+
+    /// The request headers, as sent and with sensitive headers masked.
+    pub headers: HeaderMap,
+
+    /// The size of the request body as sent, in bytes, after any compression.
+    pub body_size: usize,
+}
+
 impl<T> AsRef<T> for Response<T> {
     fn as_ref(&self) -> &T {
         &self.data
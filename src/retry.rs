@@ -193,7 +193,7 @@ pub struct RetryOnTimeout;
 
 impl RetryPredicate for RetryOnTimeout {
     fn should_retry(&self, error: &Error, _attempt: usize) -> bool {
-        matches!(error, Error::Timeout)
+        matches!(error, Error::Timeout { .. })
     }
 }
 
@@ -0,0 +1,663 @@
+//! Request signing.
+//!
+//! A [`RequestSigner`] set with [`ClientBuilder::request_signer`](crate::ClientBuilder::request_signer)
+//! signs every attempt once its URL, headers and body are final, just before it
+//! is sent. Retries and hedged requests are signed again, so timestamps in the
+//! signature stay fresh.
+//!
+//! Two signers are included: [`SigV4Signer`] for AWS Signature Version 4, and
+//! [`HmacSigner`] for webhook-style APIs that expect an HMAC over parts of the
+//! request in a header.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use calleen::signing::SigV4Signer;
+//! use calleen::Client;
+//!
+//! # async fn example() -> Result<(), calleen::Error> {
+//! let client = Client::builder()
+//!     .base_url("https://dynamodb.us-east-1.amazonaws.com")?
+//!     .request_signer(Box::new(SigV4Signer::new(
+//!         std::env::var("AWS_ACCESS_KEY_ID").unwrap(),
+//!         std::env::var("AWS_SECRET_ACCESS_KEY").unwrap(),
+//!         "us-east-1",
+//!         "dynamodb",
+//!     )))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Error, Result};
+use http::header::{HeaderName, HeaderValue, AUTHORIZATION, HOST};
+use ring::{digest, hmac};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Signs requests before they are sent.
+///
+/// The request has an absolute URI and carries every header the client adds,
+/// except for those the transport adds itself, like `Host`.
+pub trait RequestSigner: Send + Sync {
+    /// Signs `request`, typically by adding headers to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request can't be signed. The error is returned
+    /// from the call without sending the request.
+    fn sign(&self, request: &mut http::Request<Vec<u8>>) -> Result<()>;
+}
+
+/// Signs requests with AWS Signature Version 4.
+///
+/// The `host` header, `content-type` and every `x-amz-*` header are signed,
+/// along with the method, path, query and a SHA-256 hash of the body. Paths
+/// are encoded a second time, as every AWS service other than S3 expects.
+#[derive(Clone)]
+pub struct SigV4Signer {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    region: String,
+    service: String,
+}
+
+impl SigV4Signer {
+    /// Creates a signer for the given credentials, region and service name.
+    pub fn new(
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+        region: impl Into<String>,
+        service: impl Into<String>,
+    ) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+            region: region.into(),
+            service: service.into(),
+        }
+    }
+
+    /// Sets the session token of temporary credentials, sent in the
+    /// `x-amz-security-token` header.
+    pub fn session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+
+    fn sign_at(&self, request: &mut http::Request<Vec<u8>>, time: SystemTime) -> Result<()> {
+        let timestamp = Timestamp::from(time);
+        let amz_date = timestamp.iso8601_basic();
+        let date = &amz_date[..8];
+
+        let headers = request.headers_mut();
+        headers.insert("x-amz-date", header_value(&amz_date)?);
+        if let Some(token) = &self.session_token {
+            headers.insert("x-amz-security-token", header_value(token)?);
+        }
+
+        let signed_headers = signed_header_values(request, |name| {
+            name == HOST
+                || name == http::header::CONTENT_TYPE
+                || name.as_str().starts_with("x-amz-")
+        })?;
+        let canonical_request = [
+            request.method().as_str().to_string(),
+            canonical_path(request.uri().path()),
+            canonical_query(request.uri().query()),
+            signed_headers
+                .iter()
+                .map(|(name, value)| format!("{}:{}\n", name, value))
+                .collect(),
+            signed_header_names(&signed_headers),
+            hex(digest::digest(&digest::SHA256, request.body()).as_ref()),
+        ]
+        .join("\n");
+
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+        );
+
+        let key = [date, &self.region, &self.service, "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.secret_access_key).into_bytes(),
+                |key, part| hmac_sha256(&key, part.as_bytes()),
+            );
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id,
+            scope,
+            signed_header_names(&signed_headers),
+            signature
+        );
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, header_value(&authorization)?);
+        Ok(())
+    }
+}
+
+impl RequestSigner for SigV4Signer {
+    fn sign(&self, request: &mut http::Request<Vec<u8>>) -> Result<()> {
+        self.sign_at(request, SystemTime::now())
+    }
+}
+
+impl std::fmt::Debug for SigV4Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keep the secrets out of logs
+        f.debug_struct("SigV4Signer")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"[REDACTED]")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("region", &self.region)
+            .field("service", &self.service)
+            .finish()
+    }
+}
+
+/// The hash function used by an [`HmacSigner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HmacAlgorithm {
+    /// HMAC-SHA256.
+    Sha256,
+    /// HMAC-SHA384.
+    Sha384,
+    /// HMAC-SHA512.
+    Sha512,
+}
+
+/// How an [`HmacSigner`] encodes the signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureEncoding {
+    /// Lowercase hexadecimal.
+    Hex,
+    /// Standard base64 with padding.
+    Base64,
+}
+
+/// Signs requests with an HMAC over selected parts of the request.
+///
+/// The signed string joins the following with newlines:
+///
+/// 1. the method, e.g. `POST`
+/// 2. the path, e.g. `/v1/orders`
+/// 3. the query parameters sorted by name and value and percent-encoded, e.g. `a=1&b=2`
+/// 4. each [signed header](HmacSigner::signed_header) as `name:value`, in the
+///    order they were added, with the name in lowercase and an empty value for
+///    missing headers
+/// 5. the timestamp, in seconds since the Unix epoch, if a
+///    [timestamp header](HmacSigner::timestamp_header) is set
+/// 6. the SHA-256 hash of the body in lowercase hexadecimal
+///
+/// The signature is sent in the `X-Signature` header by default, in
+/// lowercase hexadecimal.
+///
+/// # Examples
+///
+/// ```
+/// use calleen::signing::{HmacAlgorithm, HmacSigner, SignatureEncoding};
+///
+/// let signer = HmacSigner::new(b"webhook secret".to_vec())
+///     .algorithm(HmacAlgorithm::Sha512)
+///     .signature_header("X-Hub-Signature")
+///     .signature_prefix("sha512=")
+///     .encoding(SignatureEncoding::Base64)
+///     .timestamp_header("X-Timestamp")
+///     .signed_header("content-type");
+/// ```
+#[derive(Clone)]
+pub struct HmacSigner {
+    key: Vec<u8>,
+    algorithm: HmacAlgorithm,
+    signature_header: String,
+    signature_prefix: String,
+    encoding: SignatureEncoding,
+    timestamp_header: Option<String>,
+    signed_headers: Vec<String>,
+}
+
+impl HmacSigner {
+    /// Creates a signer with the given secret key.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key: key.into(),
+            algorithm: HmacAlgorithm::Sha256,
+            signature_header: "X-Signature".to_string(),
+            signature_prefix: String::new(),
+            encoding: SignatureEncoding::Hex,
+            timestamp_header: None,
+            signed_headers: Vec::new(),
+        }
+    }
+
+    /// Sets the hash function. Defaults to SHA-256.
+    pub fn algorithm(mut self, algorithm: HmacAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the header the signature is sent in. Defaults to `X-Signature`.
+    pub fn signature_header(mut self, name: impl Into<String>) -> Self {
+        self.signature_header = name.into();
+        self
+    }
+
+    /// Sets a prefix for the signature header's value, e.g. `sha256=`.
+    pub fn signature_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.signature_prefix = prefix.into();
+        self
+    }
+
+    /// Sets how the signature is encoded. Defaults to hexadecimal.
+    pub fn encoding(mut self, encoding: SignatureEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sends the current time, in seconds since the Unix epoch, in the given
+    /// header and includes it in the signature.
+    pub fn timestamp_header(mut self, name: impl Into<String>) -> Self {
+        self.timestamp_header = Some(name.into());
+        self
+    }
+
+    /// Includes a header in the signature.
+    pub fn signed_header(mut self, name: impl Into<String>) -> Self {
+        self.signed_headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Returns the string that is signed for `request`.
+    fn string_to_sign(&self, request: &http::Request<Vec<u8>>, timestamp: Option<u64>) -> String {
+        let mut lines = vec![
+            request.method().as_str().to_string(),
+            request.uri().path().to_string(),
+            canonical_query(request.uri().query()),
+        ];
+        for name in &self.signed_headers {
+            let value = request
+                .headers()
+                .get(name)
+                .map(|value| canonical_header_value(value.as_bytes()))
+                .unwrap_or_default();
+            lines.push(format!("{}:{}", name, value));
+        }
+        if let Some(timestamp) = timestamp {
+            lines.push(timestamp.to_string());
+        }
+        lines.push(hex(digest::digest(&digest::SHA256, request.body()).as_ref()));
+        lines.join("\n")
+    }
+
+    fn sign_at(&self, request: &mut http::Request<Vec<u8>>, time: SystemTime) -> Result<()> {
+        let timestamp = match &self.timestamp_header {
+            Some(name) => {
+                let seconds = time
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                request
+                    .headers_mut()
+                    .insert(header_name(name)?, header_value(&seconds.to_string())?);
+                Some(seconds)
+            }
+            None => None,
+        };
+
+        let algorithm = match self.algorithm {
+            HmacAlgorithm::Sha256 => hmac::HMAC_SHA256,
+            HmacAlgorithm::Sha384 => hmac::HMAC_SHA384,
+            HmacAlgorithm::Sha512 => hmac::HMAC_SHA512,
+        };
+        let key = hmac::Key::new(algorithm, &self.key);
+        let tag = hmac::sign(&key, self.string_to_sign(request, timestamp).as_bytes());
+        let signature = match self.encoding {
+            SignatureEncoding::Hex => hex(tag.as_ref()),
+            SignatureEncoding::Base64 => base64(tag.as_ref()),
+        };
+
+        request.headers_mut().insert(
+            header_name(&self.signature_header)?,
+            header_value(&format!("{}{}", self.signature_prefix, signature))?,
+        );
+        Ok(())
+    }
+}
+
+impl RequestSigner for HmacSigner {
+    fn sign(&self, request: &mut http::Request<Vec<u8>>) -> Result<()> {
+        self.sign_at(request, SystemTime::now())
+    }
+}
+
+impl std::fmt::Debug for HmacSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keep the key out of logs
+        f.debug_struct("HmacSigner")
+            .field("key", &"[REDACTED]")
+            .field("algorithm", &self.algorithm)
+            .field("signature_header", &self.signature_header)
+            .field("signature_prefix", &self.signature_prefix)
+            .field("encoding", &self.encoding)
+            .field("timestamp_header", &self.timestamp_header)
+            .field("signed_headers", &self.signed_headers)
+            .finish()
+    }
+}
+
+/// Returns the canonical values of the headers selected by `include`, sorted by
+/// name, including a `host` header derived from the URI if there isn't one.
+fn signed_header_values(
+    request: &http::Request<Vec<u8>>,
+    include: impl Fn(&HeaderName) -> bool,
+) -> Result<Vec<(String, String)>> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for name in request.headers().keys().filter(|name| include(name)) {
+        let values = request
+            .headers()
+            .get_all(name)
+            .iter()
+            .map(|value| canonical_header_value(value.as_bytes()))
+            .collect::<Vec<_>>();
+        headers.push((name.as_str().to_string(), values.join(",")));
+    }
+
+    if !request.headers().contains_key(HOST) && include(&HOST) {
+        let host = request.uri().authority().ok_or_else(|| {
+            Error::ConfigurationError("Can't sign a request without a host".to_string())
+        })?;
+        headers.push((HOST.as_str().to_string(), host.as_str().to_string()));
+    }
+
+    headers.sort();
+    Ok(headers)
+}
+
+fn signed_header_names(headers: &[(String, String)]) -> String {
+    headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Trims a header value and collapses runs of spaces.
+fn canonical_header_value(value: &[u8]) -> String {
+    String::from_utf8_lossy(value)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Percent-encodes each segment of an already encoded path again.
+fn canonical_path(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Decodes the query parameters and re-encodes them sorted by name and value.
+fn canonical_query(query: Option<&str>) -> String {
+    let mut pairs = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .map(|(name, value)| (uri_encode(&name), uri_encode(&value)))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encodes everything but unreserved characters, as RFC 3986 describes.
+fn uri_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, data).as_ref().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn header_name(name: &str) -> Result<HeaderName> {
+    HeaderName::try_from(name)
+        .map_err(|e| Error::ConfigurationError(format!("Invalid signature header: {}", e)))
+}
+
+fn header_value(value: &str) -> Result<HeaderValue> {
+    HeaderValue::try_from(value)
+        .map_err(|e| Error::ConfigurationError(format!("Invalid signature header value: {}", e)))
+}
+
+/// A UTC date and time.
+struct Timestamp {
+    year: i64,
+    month: u32,
+    day: u32,
+    seconds_of_day: u64,
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        let seconds = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        // Converts days since the epoch to a civil date, see
+        // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = (seconds / 86400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            seconds_of_day: seconds % 86400,
+        }
+    }
+}
+
+impl Timestamp {
+    /// Formats the timestamp as `YYYYMMDDTHHMMSSZ`.
+    fn iso8601_basic(&self) -> String {
+        format!(
+            "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+            self.year,
+            self.month,
+            self.day,
+            self.seconds_of_day / 3600,
+            self.seconds_of_day / 60 % 60,
+            self.seconds_of_day % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 2015-08-30T12:36:00Z, the time used by the AWS SigV4 test suite.
+    fn test_suite_time() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_440_938_160)
+    }
+
+    fn request(method: &str, uri: &str, body: &[u8]) -> http::Request<Vec<u8>> {
+        http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(body.to_vec())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_timestamp_format() {
+        assert_eq!(
+            Timestamp::from(test_suite_time()).iso8601_basic(),
+            "20150830T123600Z"
+        );
+        assert_eq!(
+            Timestamp::from(UNIX_EPOCH).iso8601_basic(),
+            "19700101T000000Z"
+        );
+        assert_eq!(
+            Timestamp::from(UNIX_EPOCH + Duration::from_secs(951_827_696)).iso8601_basic(),
+            "20000229T123456Z"
+        );
+    }
+
+    #[test]
+    fn test_sigv4_get_vanilla() {
+        let signer = SigV4Signer::new(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "us-east-1",
+            "service",
+        );
+        let mut request = request("GET", "https://example.amazonaws.com/", b"");
+        signer.sign_at(&mut request, test_suite_time()).unwrap();
+
+        assert_eq!(request.headers()["x-amz-date"], "20150830T123600Z");
+        assert_eq!(
+            request.headers()[AUTHORIZATION],
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_sigv4_session_token_is_signed() {
+        let signer = SigV4Signer::new("AKIDEXAMPLE", "hunter2", "us-east-1", "service")
+            .session_token("session-token");
+        let mut request = request("GET", "https://example.amazonaws.com/", b"");
+        signer.sign_at(&mut request, test_suite_time()).unwrap();
+
+        assert_eq!(request.headers()["x-amz-security-token"], "session-token");
+        let authorization = request.headers()[AUTHORIZATION].to_str().unwrap();
+        assert!(authorization.contains("SignedHeaders=host;x-amz-date;x-amz-security-token"));
+        let debug = format!("{:?}", signer);
+        assert!(!debug.contains("hunter2") && !debug.contains("session-token"));
+    }
+
+    #[test]
+    fn test_canonical_query_and_path() {
+        assert_eq!(
+            canonical_query(Some("b=2&a=2&a=1&c=x+y&d=%2F")),
+            "a=1&a=2&b=2&c=x%20y&d=%2F"
+        );
+        assert_eq!(canonical_query(None), "");
+        assert_eq!(canonical_path("/a%20b/c"), "/a%2520b/c");
+        assert_eq!(canonical_path(""), "/");
+    }
+
+    #[test]
+    fn test_hmac_string_to_sign() {
+        let signer = HmacSigner::new(b"key".to_vec())
+            .signed_header("Content-Type")
+            .signed_header("x-missing");
+        let mut request = request("POST", "https://api.example.com/orders?b=2&a=1", b"{}");
+        request
+            .headers_mut()
+            .insert("content-type", HeaderValue::from_static("application/json"));
+
+        assert_eq!(
+            signer.string_to_sign(&request, Some(1_440_938_160)),
+            "POST\n/orders\na=1&b=2\ncontent-type:application/json\nx-missing:\n1440938160\n\
+             44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+    }
+
+    #[test]
+    fn test_hmac_signature() {
+        let signer = HmacSigner::new(b"key".to_vec())
+            .signature_prefix("sha256=")
+            .timestamp_header("X-Timestamp");
+        let mut request = request("GET", "https://api.example.com/", b"");
+        signer.sign_at(&mut request, test_suite_time()).unwrap();
+
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"key");
+        let expected = hmac::sign(
+            &key,
+            signer
+                .string_to_sign(&request, Some(1_440_938_160))
+                .as_bytes(),
+        );
+        assert_eq!(request.headers()["x-timestamp"], "1440938160");
+        assert_eq!(
+            request.headers()["x-signature"],
+            format!("sha256={}", hex(expected.as_ref())).as_str()
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -0,0 +1,331 @@
+//! TLS settings for the default transport.
+//!
+//! [`TlsConfig`] adds private root CAs, client certificates for mutual TLS, a
+//! minimum TLS version and SPKI pinning to the
+//! [`ReqwestTransport`](crate::transport::ReqwestTransport) that
+//! [`ClientBuilder::build`](crate::ClientBuilder::build) creates. Without it,
+//! servers are verified against the built-in Mozilla root certificates.
+//!
+//! Certificates and keys are parsed when the client is built, so invalid ones
+//! fail [`ClientBuilder::build`](crate::ClientBuilder::build) with
+//! [`Error::ConfigurationError`].
+
+use crate::{Error, Result};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use std::sync::Arc;
+
+/// TLS settings for connecting to servers.
+///
+/// # Examples
+///
+/// ```no_run
+/// use calleen::tls::{Certificate, Identity, TlsConfig, TlsVersion};
+///
+/// # fn example() -> std::io::Result<()> {
+/// let config = TlsConfig::builder()
+///     .root_certificate(Certificate::Pem(std::fs::read("internal-ca.pem")?))
+///     .built_in_roots(false)
+///     .identity(Identity::from_pem(
+///         std::fs::read("client.pem")?,
+///         std::fs::read("client.key")?,
+///     ))
+///     .min_version(TlsVersion::Tls13)
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// Additional root certificates to trust.
+    pub root_certificates: Vec<Certificate>,
+
+    /// Whether to trust the built-in Mozilla root certificates. Defaults to `true`.
+    ///
+    /// Disable this to only trust [`root_certificates`](TlsConfig::root_certificates).
+    pub built_in_roots: bool,
+
+    /// The client certificate and key to present for mutual TLS.
+    pub identity: Option<Identity>,
+
+    /// The minimum TLS version to negotiate. Defaults to TLS 1.2.
+    pub min_version: TlsVersion,
+
+    /// SHA-256 hashes of the DER-encoded `SubjectPublicKeyInfo` of pinned keys.
+    ///
+    /// When non-empty, a server is only trusted if its certificate chain passes
+    /// the usual verification *and* the key of at least one certificate the
+    /// server presents is pinned. Servers usually present their own certificate
+    /// and any intermediates, but not the root. Use [`spki_sha256`] to compute a pin.
+    pub pinned_spki_sha256: Vec<[u8; 32]>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            root_certificates: Vec::new(),
+            built_in_roots: true,
+            identity: None,
+            min_version: TlsVersion::Tls12,
+            pinned_spki_sha256: Vec::new(),
+        }
+    }
+}
+
+impl TlsConfig {
+    /// Creates a new builder for configuring TLS.
+    pub fn builder() -> TlsConfigBuilder {
+        TlsConfigBuilder {
+            config: Self::default(),
+        }
+    }
+
+    /// Builds the rustls configuration for these settings.
+    pub(crate) fn client_config(&self) -> Result<rustls::ClientConfig> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let mut roots = RootCertStore::empty();
+        if self.built_in_roots {
+            roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        }
+        for certificate in &self.root_certificates {
+            for der in certificate.to_der()? {
+                roots
+                    .add(der)
+                    .map_err(|e| tls_error("Invalid root certificate", e))?;
+            }
+        }
+
+        let versions: &[&rustls::SupportedProtocolVersion] = match self.min_version {
+            TlsVersion::Tls12 => &[&rustls::version::TLS13, &rustls::version::TLS12],
+            TlsVersion::Tls13 => &[&rustls::version::TLS13],
+        };
+        let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(versions)
+            .map_err(|e| tls_error("Unsupported TLS versions", e))?;
+
+        let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider)
+            .build()
+            .map_err(|e| tls_error("Invalid root certificates", e))?;
+        let builder = if self.pinned_spki_sha256.is_empty() {
+            builder.with_webpki_verifier(verifier)
+        } else {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedVerifier {
+                    inner: verifier,
+                    pins: self.pinned_spki_sha256.clone(),
+                }))
+        };
+
+        let mut config = match &self.identity {
+            Some(identity) => {
+                let chain = Certificate::Pem(identity.cert_chain_pem.clone()).to_der()?;
+                let key = PrivateKeyDer::from_pem_slice(&identity.key_pem)
+                    .map_err(|e| tls_error("Invalid client key", e))?;
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .map_err(|e| tls_error("Invalid client identity", e))?
+            }
+            None => builder.with_no_client_auth(),
+        };
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(config)
+    }
+}
+
+/// Builder for `TlsConfig`.
+pub struct TlsConfigBuilder {
+    config: TlsConfig,
+}
+
+impl TlsConfigBuilder {
+    /// Adds a root certificate, or a bundle of them in PEM format, to trust.
+    pub fn root_certificate(mut self, certificate: Certificate) -> Self {
+        self.config.root_certificates.push(certificate);
+        self
+    }
+
+    /// Sets whether to trust the built-in Mozilla root certificates.
+    pub fn built_in_roots(mut self, enabled: bool) -> Self {
+        self.config.built_in_roots = enabled;
+        self
+    }
+
+    /// Sets the client certificate and key to present for mutual TLS.
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.config.identity = Some(identity);
+        self
+    }
+
+    /// Sets the minimum TLS version to negotiate.
+    pub fn min_version(mut self, version: TlsVersion) -> Self {
+        self.config.min_version = version;
+        self
+    }
+
+    /// Pins a key by the SHA-256 hash of its DER-encoded `SubjectPublicKeyInfo`.
+    pub fn pin_spki_sha256(mut self, hash: [u8; 32]) -> Self {
+        self.config.pinned_spki_sha256.push(hash);
+        self
+    }
+
+    /// Builds the `TlsConfig`.
+    pub fn build(self) -> TlsConfig {
+        self.config
+    }
+}
+
+/// An X.509 certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Certificate {
+    /// One or more PEM-encoded certificates.
+    Pem(Vec<u8>),
+    /// A single DER-encoded certificate.
+    Der(Vec<u8>),
+}
+
+impl Certificate {
+    fn to_der(&self) -> Result<Vec<CertificateDer<'static>>> {
+        match self {
+            Certificate::Pem(pem) => {
+                let certificates = CertificateDer::pem_slice_iter(pem)
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|e| tls_error("Invalid PEM certificate", e))?;
+                if certificates.is_empty() {
+                    return Err(Error::ConfigurationError(
+                        "No certificates found in PEM".to_string(),
+                    ));
+                }
+                Ok(certificates)
+            }
+            Certificate::Der(der) => Ok(vec![CertificateDer::from(der.clone())]),
+        }
+    }
+}
+
+/// A client certificate chain and private key for mutual TLS.
+#[derive(Clone)]
+pub struct Identity {
+    cert_chain_pem: Vec<u8>,
+    key_pem: Vec<u8>,
+}
+
+impl Identity {
+    /// Creates an identity from a PEM-encoded certificate chain, leaf first, and
+    /// a PEM-encoded PKCS#8, PKCS#1 or SEC1 private key.
+    pub fn from_pem(cert_chain_pem: impl Into<Vec<u8>>, key_pem: impl Into<Vec<u8>>) -> Self {
+        Self {
+            cert_chain_pem: cert_chain_pem.into(),
+            key_pem: key_pem.into(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keep the private key out of logs
+        f.debug_struct("Identity")
+            .field(
+                "cert_chain_pem",
+                &String::from_utf8_lossy(&self.cert_chain_pem),
+            )
+            .field("key_pem", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// A TLS protocol version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    /// TLS 1.2.
+    Tls12,
+    /// TLS 1.3.
+    Tls13,
+}
+
+/// Returns the SHA-256 hash of the `SubjectPublicKeyInfo` of a DER-encoded
+/// certificate, for use as a pin.
+///
+/// # Errors
+///
+/// Returns an error if the certificate can't be parsed.
+pub fn spki_sha256(certificate_der: &[u8]) -> Result<[u8; 32]> {
+    let der = CertificateDer::from(certificate_der);
+    let certificate =
+        webpki::EndEntityCert::try_from(&der).map_err(|e| tls_error("Invalid certificate", e))?;
+    let digest = ring::digest::digest(
+        &ring::digest::SHA256,
+        certificate.subject_public_key_info().as_ref(),
+    );
+
+    let mut hash = [0; 32];
+    hash.copy_from_slice(digest.as_ref());
+    Ok(hash)
+}
+
+fn tls_error(context: &str, error: impl std::fmt::Display) -> Error {
+    Error::ConfigurationError(format!("{}: {}", context, error))
+}
+
+/// Verifies certificates as usual, then requires a pinned key in the chain.
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .filter_map(|certificate| spki_sha256(certificate).ok())
+            .any(|hash| self.pins.contains(&hash));
+        if !pinned {
+            return Err(rustls::Error::General(
+                "no certificate in the chain matches a pinned key".to_string(),
+            ));
+        }
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
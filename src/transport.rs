@@ -0,0 +1,149 @@
+//! The layer that sends HTTP requests over the network.
+//!
+//! The client builds each attempt as an [`http::Request`] and hands it to a
+//! [`Transport`], which returns the [`http::Response`] with its body fully read.
+//! Retries, hedging, rate limiting and timeouts are all applied by the client
+//! around the transport, so they work the same with any transport.
+//!
+//! [`ReqwestTransport`] is used by default. Implement [`Transport`] to send
+//! requests another way, for example with hyper directly or to an in-process
+//! service, and pass it to [`ClientBuilder::transport`](crate::ClientBuilder::transport).
+//!
+//! # Examples
+//!
+//! ```
+//! use calleen::transport::{Transport, TransportFuture};
+//! use calleen::Client;
+//!
+//! /// Answers every request with its own path.
+//! struct Echo;
+//!
+//! impl Transport for Echo {
+//!     fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+//!         let path = request.uri().path().to_string();
+//!         Box::pin(async move { Ok(http::Response::new(format!("\"{}\"", path).into_bytes())) })
+//!     }
+//! }
+//!
+//! # async fn example() -> Result<(), calleen::Error> {
+//! let client = Client::builder()
+//!     .base_url("http://echo.local")?
+//!     .transport(Box::new(Echo))
+//!     .build()?;
+//!
+//! let response = client.get::<String>("/hello").await?;
+//! assert_eq!(response.data, "/hello");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::dns::DnsError;
+use crate::error::{NetworkError, NetworkErrorKind};
+use crate::{Error, Result};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// The future returned by [`Transport::send`].
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<http::Response<Vec<u8>>>> + Send + 'a>>;
+
+/// Sends a single HTTP request and reads the full response.
+///
+/// The request URI is absolute, and the request already carries every header
+/// the client adds. A transport should report failures to reach the server as
+/// [`Error::Network`]; non-2xx responses are returned as `Ok` and turned into
+/// errors by the client.
+///
+/// A transport that reads the body after receiving the headers can insert a
+/// [`BodyReadTime`] into the response's extensions, so that
+/// [`Timings`](crate::Timings) tell the two apart.
+pub trait Transport: Send + Sync {
+    /// Sends `request`, returning the response with its body read into memory.
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_>;
+}
+
+/// How long a transport took to read a response body, in the response's
+/// extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyReadTime(pub Duration);
+
+/// The default [`Transport`], backed by a `reqwest::Client`.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Creates a transport that sends requests with `client`.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let request = reqwest::Request::try_from(request).map_err(network_error)?;
+            let response = self.client.execute(request).await.map_err(network_error)?;
+
+            let mut builder = http::Response::builder()
+                .status(response.status())
+                .version(response.version());
+            if let Some(headers) = builder.headers_mut() {
+                *headers = response.headers().clone();
+            }
+            let body_start = Instant::now();
+            let body = response.bytes().await.map_err(network_error)?;
+            let builder = builder.extension(BodyReadTime(body_start.elapsed()));
+
+            builder
+                .body(body.to_vec())
+                .map_err(|e| NetworkError::new(NetworkErrorKind::Other, e.to_string()).into())
+        })
+    }
+}
+
+/// Converts a reqwest error into an [`Error`], keeping it as the source.
+fn network_error(error: reqwest::Error) -> Error {
+    if error.is_timeout() {
+        return Error::Timeout { request: None };
+    }
+
+    let kind = if is_dns_error(&error) {
+        NetworkErrorKind::Dns
+    } else if error.is_connect() {
+        NetworkErrorKind::Connect
+    } else if error.is_body() || error.is_decode() {
+        NetworkErrorKind::Body
+    } else if error.is_request() {
+        NetworkErrorKind::Request
+    } else {
+        NetworkErrorKind::Other
+    };
+
+    // reqwest's message only describes the outermost error, so add the causes
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    Error::Network(NetworkError::new(kind, message).with_source(error))
+}
+
+/// Returns `true` if `error` was caused by a failure to resolve the hostname.
+///
+/// Failures of calleen's resolver are a [`DnsError`]. Those of reqwest's own
+/// resolver, or of one set on a preconfigured builder, are only recognizable by
+/// the message of the connector error wrapping them, which isn't exported.
+fn is_dns_error(error: &reqwest::Error) -> bool {
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        if cause.is::<DnsError>() || cause.to_string() == "dns error" {
+            return true;
+        }
+        source = cause.source();
+    }
+    false
+}
@@ -0,0 +1,224 @@
+//! Integration tests for request and response compression.
+
+#![cfg(all(
+    feature = "gzip",
+    feature = "deflate",
+    feature = "brotli",
+    feature = "zstd"
+))]
+
+use calleen::compression::{CompressionConfig, Encoding};
+use calleen::Client;
+use std::io::{Read, Write};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn encode(encoding: &str, data: &[u8]) -> Vec<u8> {
+    match encoding {
+        "gzip" => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        }
+        "deflate" => {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        }
+        "br" => {
+            let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+            encoder.write_all(data).unwrap();
+            encoder.into_inner()
+        }
+        "zstd" => zstd::encode_all(data, 3).unwrap(),
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn test_response_decompression() {
+    let mock_server = MockServer::start().await;
+    let body = br#"{"id": 1, "name": "Test"}"#;
+
+    for encoding in ["gzip", "deflate", "br", "zstd"] {
+        Mock::given(method("GET"))
+            .and(path(format!("/{}", encoding)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", encoding)
+                    .insert_header("content-type", "application/json")
+                    .set_body_bytes(encode(encoding, body)),
+            )
+            .mount(&mock_server)
+            .await;
+    }
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    for encoding in ["gzip", "deflate", "br", "zstd"] {
+        let response = client
+            .get::<serde_json::Value>(format!("/{}", encoding))
+            .await
+            .unwrap();
+        assert_eq!(response.data["id"], 1);
+        assert_eq!(response.raw_body.as_bytes(), body);
+        assert!(response.header("content-encoding").is_none());
+    }
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(
+        requests[0].headers["accept-encoding"],
+        "zstd, br, gzip, deflate"
+    );
+}
+
+#[tokio::test]
+async fn test_error_body_is_decompressed() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .insert_header("content-encoding", "gzip")
+                .set_body_bytes(encode("gzip", b"invalid id")),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let error = client.get::<()>("/users/x").await.unwrap_err();
+    assert_eq!(error.raw_response(), Some("invalid id"));
+}
+
+#[tokio::test]
+async fn test_decompressed_size_is_limited() {
+    let mock_server = MockServer::start().await;
+
+    for encoding in ["gzip", "deflate", "br", "zstd"] {
+        Mock::given(method("GET"))
+            .and(path(format!("/{}", encoding)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", encoding)
+                    .set_body_bytes(encode(encoding, &[b' '; 10_000])),
+            )
+            .mount(&mock_server)
+            .await;
+    }
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(calleen::RetryStrategy::None)
+        .compression_config(
+            CompressionConfig::builder()
+                .max_decompressed_size(1000)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    for encoding in ["gzip", "deflate", "br", "zstd"] {
+        let error = client
+            .get::<()>(format!("/{}", encoding))
+            .await
+            .unwrap_err();
+        let calleen::Error::MaxRetriesExceeded { last_error, .. } = error else {
+            panic!("expected max retries exceeded, got {:?}", error);
+        };
+        match *last_error {
+            calleen::Error::Network(e) => assert_eq!(e.kind(), calleen::NetworkErrorKind::Body),
+            other => panic!("expected a body error, got {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_decompression_disabled() {
+    let mock_server = MockServer::start().await;
+    let compressed = encode("gzip", b"\"text\"");
+
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-encoding", "gzip")
+                .set_body_bytes(compressed.clone()),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .compression_config(
+            CompressionConfig::builder()
+                .decompress_responses(false)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let error = client.get::<String>("/").await.unwrap_err();
+    assert!(matches!(
+        error,
+        calleen::Error::DeserializationFailed { .. }
+    ));
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("accept-encoding").is_none());
+}
+
+#[tokio::test]
+async fn test_request_compression() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .compression_config(
+            CompressionConfig::builder()
+                .compress_requests(Encoding::Gzip)
+                .min_request_size(100)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let small = serde_json::json!({"id": 1});
+    let large = serde_json::json!({"items": vec!["item"; 100]});
+    client.post::<_, ()>("/small", &small).await.unwrap();
+    client.post::<_, ()>("/large", &large).await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("content-encoding").is_none());
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&requests[0].body).unwrap(),
+        small
+    );
+
+    assert_eq!(requests[1].headers["content-encoding"], "gzip");
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(&requests[1].body[..])
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&decoded).unwrap(),
+        large
+    );
+}
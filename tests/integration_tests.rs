@@ -1,12 +1,26 @@
 //! Integration tests using wiremock to simulate HTTP servers.
 
+use calleen::balance::{BalanceConfig, BalanceStrategy};
+use calleen::connection::{ConnectionConfig, Proxy};
+use calleen::cookie::CookieJar;
+use calleen::dns::{Resolve, Resolving};
+use calleen::hedge::{HedgeConfig, HedgeDelay};
+use calleen::logging::{LogConfig, LogLevel};
+use calleen::metrics::{MetricLabels, MetricsRecorder};
+use calleen::pagination::{PageScheme, PaginationConfig};
+use calleen::rate_limit::{MaxWaitPolicy, RateLimitConfig};
+use calleen::redact::RedactionPolicy;
+use calleen::redirect::{CrossOriginRedirects, RedirectPolicy};
 use calleen::retry::RetryPredicate;
-use calleen::{Client, Error, RetryStrategy};
+use calleen::signing::{HmacSigner, RequestSigner};
+use calleen::transport::{Transport, TransportFuture};
+use calleen::{Client, Either, Error, NetworkErrorKind, RetryStrategy};
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -133,10 +147,14 @@ async fn test_deserialization_error() {
             raw_response,
             serde_error,
             status,
+            request,
         }) => {
             assert_eq!(status.as_u16(), 200);
             assert_eq!(raw_response, "invalid json");
             assert!(serde_error.contains("expected"));
+            let request = request.unwrap();
+            assert_eq!(request.method, http::Method::GET);
+            assert_eq!(request.url.path(), "/test");
         }
         _ => panic!("Expected DeserializationFailed, got {:?}", result),
     }
@@ -210,9 +228,22 @@ async fn test_max_retries_exceeded() {
     let result = client.get::<TestData>("/test").await;
 
     match result {
-        Err(Error::MaxRetriesExceeded { attempts, .. }) => {
+        Err(Error::MaxRetriesExceeded {
+            attempts,
+            ref attempt_history,
+            ..
+        }) => {
             // max_retries: 2 means we try 2 retries, so 3 total attempts (1 initial + 2 retries)
             assert_eq!(attempts, 3);
+            assert_eq!(attempt_history.len(), 3);
+            for (i, attempt) in attempt_history.iter().enumerate() {
+                assert_eq!(attempt.number, i + 1);
+                assert_eq!(
+                    attempt.status,
+                    Some(http::StatusCode::INTERNAL_SERVER_ERROR)
+                );
+                assert!(attempt.error.is_some());
+            }
         }
         _ => panic!("Expected MaxRetriesExceeded, got {:?}", result),
     }
@@ -449,18 +480,9 @@ async fn test_all_http_methods() {
         .await
         .unwrap();
 
-    // Test DELETE  (returns empty body, so use serde_json::Value or handle empty response)
-    // For empty responses, we can't deserialize, so we expect an error or use a permissive type
-    let delete_result = client.delete::<serde_json::Value>("/test").await;
-    // DELETE with 204 returns empty body, which causes deserialization error
-    // This is actually expected behavior - let's just verify we got a 204
-    match delete_result {
-        Err(Error::DeserializationFailed { status, .. }) => {
-            assert_eq!(status.as_u16(), 204);
-        }
-        Ok(_) => panic!("Unexpected success for empty DELETE response"),
-        Err(e) => panic!("Unexpected error: {:?}", e),
-    }
+    // Test DELETE (returns an empty body)
+    let delete_response = client.delete::<()>("/test").await.unwrap();
+    assert_eq!(delete_response.status, 204);
 
     // Test PATCH
     let _ = client
@@ -476,6 +498,7 @@ async fn test_error_is_retryable() {
         raw_response: "Error".to_string().into_boxed_str(),
         headers: Box::new(http::HeaderMap::new()),
         rate_limit_info: None,
+        request: None,
     };
     assert!(error_5xx.is_retryable());
 
@@ -484,10 +507,11 @@ async fn test_error_is_retryable() {
         raw_response: "Error".to_string().into_boxed_str(),
         headers: Box::new(http::HeaderMap::new()),
         rate_limit_info: None,
+        request: None,
     };
     assert!(!error_4xx.is_retryable());
 
-    let error_timeout = Error::Timeout;
+    let error_timeout = Error::Timeout { request: None };
     assert!(error_timeout.is_retryable());
 
     let error_config = Error::ConfigurationError("Error".to_string());
@@ -695,3 +719,2134 @@ async fn test_rate_limit_max_wait_cap() {
     assert!(elapsed >= Duration::from_secs(2));
     assert!(elapsed < Duration::from_secs(4));
 }
+
+#[tokio::test]
+async fn test_hedging_skips_non_idempotent_methods() {
+    let mock_server = MockServer::start().await;
+
+    let response_data = TestData {
+        id: 1,
+        name: "Test".to_string(),
+    };
+
+    Mock::given(method("POST"))
+        .and(path("/test"))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .set_body_json(&response_data)
+                .set_delay(Duration::from_millis(200)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .hedge_config(
+            HedgeConfig::builder()
+                .delay(HedgeDelay::Fixed(Duration::from_millis(10)))
+                .max_hedges(3)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let response = client
+        .post::<TestData, TestData>("/test", &response_data)
+        .await
+        .unwrap();
+
+    assert_eq!(response.hedges, 0);
+}
+
+#[derive(Clone, Default)]
+struct EventRecorder {
+    events: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl EventRecorder {
+    fn events(&self) -> Vec<String> {
+        self.events.lock().unwrap().clone()
+    }
+
+    fn push(&self, event: String) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+impl MetricsRecorder for EventRecorder {
+    fn record_call(
+        &self,
+        labels: &MetricLabels<'_>,
+        status: Option<http::StatusCode>,
+        _latency: Duration,
+    ) {
+        self.push(format!(
+            "call {} {} {}",
+            labels.method,
+            labels.endpoint,
+            calleen::metrics::status_label(status)
+        ));
+    }
+
+    fn record_attempt(
+        &self,
+        labels: &MetricLabels<'_>,
+        status: Option<http::StatusCode>,
+        _latency: Duration,
+    ) {
+        self.push(format!(
+            "attempt {} {}",
+            labels.endpoint,
+            calleen::metrics::status_label(status)
+        ));
+    }
+
+    fn record_retry(&self, labels: &MetricLabels<'_>, attempt: usize) {
+        self.push(format!("retry {} {}", labels.endpoint, attempt));
+    }
+
+    fn record_rate_limit_wait(&self, labels: &MetricLabels<'_>, wait: Duration) {
+        self.push(format!(
+            "rate_limit_wait {} {}",
+            labels.endpoint,
+            wait.as_secs()
+        ));
+    }
+
+    fn record_deserialization_failure(&self, labels: &MetricLabels<'_>, status: http::StatusCode) {
+        self.push(format!(
+            "deserialization_failure {} {}",
+            labels.endpoint, status
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_metrics_recorded_for_retries() {
+    let mock_server = MockServer::start().await;
+    let attempt_count = Arc::new(AtomicUsize::new(0));
+    let attempt_count_clone = attempt_count.clone();
+
+    let response_data = TestData {
+        id: 1,
+        name: "Test".to_string(),
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/users/1"))
+        .respond_with(move |_req: &wiremock::Request| {
+            match attempt_count_clone.fetch_add(1, Ordering::SeqCst) {
+                0 => ResponseTemplate::new(503),
+                1 => ResponseTemplate::new(429).insert_header("retry-after", "0"),
+                _ => ResponseTemplate::new(200).set_body_json(&response_data),
+            }
+        })
+        .mount(&mock_server)
+        .await;
+
+    let recorder = EventRecorder::default();
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 3,
+        })
+        .metrics_recorder(Box::new(recorder.clone()))
+        .build()
+        .unwrap();
+
+    let metadata = calleen::metadata::RequestMetadata::new(http::Method::GET, "/users/1")
+        .with_endpoint("/users/{id}");
+    client.call::<(), TestData>(metadata, None).await.unwrap();
+
+    assert_eq!(
+        recorder.events(),
+        vec![
+            "attempt /users/{id} 503",
+            "retry /users/{id} 1",
+            "attempt /users/{id} 429",
+            "rate_limit_wait /users/{id} 0",
+            "retry /users/{id} 2",
+            "attempt /users/{id} 200",
+            "call GET /users/{id} 200",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_recorded_for_deserialization_failure() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("invalid json"))
+        .mount(&mock_server)
+        .await;
+
+    let recorder = EventRecorder::default();
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .metrics_recorder(Box::new(recorder.clone()))
+        .build()
+        .unwrap();
+
+    let _ = client.get::<TestData>("/test").await;
+
+    assert_eq!(
+        recorder.events(),
+        vec![
+            "attempt /test 200",
+            "deserialization_failure /test 200 OK",
+            "call GET /test 200",
+        ]
+    );
+}
+
+/// Captures formatted log output for assertions.
+#[derive(Clone, Default)]
+struct LogCapture {
+    buffer: Arc<std::sync::Mutex<Vec<u8>>>,
+}
+
+impl LogCapture {
+    fn contents(&self) -> String {
+        String::from_utf8(self.buffer.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl std::io::Write for LogCapture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogCapture {
+    type Writer = LogCapture;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[tokio::test]
+async fn test_redaction_of_errors_and_logs() {
+    let logs = LogCapture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(
+            ResponseTemplate::new(401)
+                .insert_header("set-cookie", "session=secret-session")
+                .set_body_string(format!(
+                    r#"{{"error":"unauthorized","token":"secret-token","detail":"{}"}}"#,
+                    "x".repeat(100)
+                )),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .redaction_policy(
+            RedactionPolicy::builder()
+                .mask_field("$.token")
+                .max_body_len(60)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let error = client.get::<TestData>("/test").await.unwrap_err();
+
+    match &error {
+        Error::HttpError {
+            raw_response,
+            headers,
+            ..
+        } => {
+            assert!(raw_response.contains(r#""token":"[REDACTED]""#));
+            assert_eq!(headers["set-cookie"], "[REDACTED]");
+        }
+        _ => panic!("Expected HttpError, got {:?}", error),
+    }
+    assert!(!error.to_string().contains("secret-token"));
+    assert!(!format!("{:?}", error).contains("secret-session"));
+
+    let logs = logs.contents();
+    assert!(logs.contains("Client error (4xx)"));
+    assert!(logs.contains("bytes truncated"));
+    assert!(!logs.contains("secret-token"));
+    assert!(!logs.contains(&"x".repeat(100)));
+}
+
+#[tokio::test]
+async fn test_oversized_error_bodies_are_truncated() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/error"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("e".repeat(10_000)))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/invalid"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("i".repeat(10_000)))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .redaction_policy(RedactionPolicy::builder().max_body_len(100).build())
+        .build()
+        .unwrap();
+
+    let error = client.get::<TestData>("/error").await.unwrap_err();
+    match &error {
+        Error::HttpError { raw_response, .. } => {
+            assert_eq!(
+                raw_response.as_ref(),
+                format!("{}... (9900 bytes truncated)", "e".repeat(100))
+            );
+        }
+        _ => panic!("Expected HttpError, got {:?}", error),
+    }
+    assert!(error.to_string().len() < 200);
+
+    let error = client.get::<TestData>("/invalid").await.unwrap_err();
+    match &error {
+        Error::DeserializationFailed { raw_response, .. } => {
+            assert!(raw_response.ends_with("... (9900 bytes truncated)"));
+        }
+        _ => panic!("Expected DeserializationFailed, got {:?}", error),
+    }
+}
+
+#[test]
+fn test_request_metadata_debug_redacts_headers() {
+    let metadata = calleen::metadata::RequestMetadata::new(http::Method::GET, "/test")
+        .with_header("Authorization", "Bearer secret")
+        .unwrap()
+        .with_header("Accept", "application/json")
+        .unwrap()
+        .with_header("X-Api-Key", "custom-secret")
+        .unwrap();
+
+    // Headers only a client's policy would know are sensitive are redacted too
+    let debug = format!("{:?}", metadata);
+    assert!(!debug.contains("secret"));
+    assert!(debug.contains("[REDACTED]"));
+    assert!(debug.contains("application/json"));
+}
+
+#[tokio::test]
+async fn test_status_log_levels() {
+    let logs = LogCapture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/conflict"))
+        .respond_with(ResponseTemplate::new(409).set_body_string("conflict"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .log_config(
+            LogConfig::builder()
+                .status_level(404, LogLevel::Off)
+                .client_error(LogLevel::Warn)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let error = client.get::<TestData>("/missing").await.unwrap_err();
+    assert_eq!(error.status(), Some(http::StatusCode::NOT_FOUND));
+    assert!(logs.contents().is_empty());
+
+    client.get::<TestData>("/conflict").await.unwrap_err();
+    let contents = logs.contents();
+    assert!(contents.contains("WARN"));
+    assert!(contents.contains("Client error (4xx)"));
+    assert!(!contents.contains("ERROR"));
+
+    // Request overrides take precedence over the client's levels
+    let metadata = calleen::metadata::RequestMetadata::new(http::Method::GET, "/missing")
+        .with_log_level(404, LogLevel::Error);
+    client
+        .call::<(), TestData>(metadata, None)
+        .await
+        .unwrap_err();
+    assert!(logs.contents().contains("ERROR"));
+}
+
+#[tokio::test]
+async fn test_request_logging() {
+    let logs = LogCapture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .with_max_level(tracing::Level::DEBUG)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .default_header("Authorization", "Bearer secret-token")
+        .unwrap()
+        .default_header("x-tag", "from-client")
+        .unwrap()
+        .redaction_policy(RedactionPolicy::builder().mask_field("$.password").build())
+        .log_config(
+            LogConfig::builder()
+                .log_request_headers(true)
+                .log_request_body(true)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let metadata = calleen::metadata::RequestMetadata::new(http::Method::POST, "/login")
+        .with_header("x-tag", "from-request")
+        .unwrap();
+    client
+        .call::<_, serde_json::Value>(
+            metadata,
+            Some(&serde_json::json!({"user": "alice", "password": "hunter2"})),
+        )
+        .await
+        .unwrap();
+
+    // The logged headers are the ones sent, including both values of a repeated header
+    let contents = logs.contents();
+    assert!(contents.contains("Request headers"));
+    assert!(contents.contains(r#""x-tag": "from-client", "x-tag": "from-request""#));
+    assert!(contents.contains(r#""content-type": "application/json""#));
+    assert!(contents.contains("Request body"));
+    assert!(contents.contains("alice"));
+    assert!(!contents.contains("secret-token"));
+    assert!(!contents.contains("hunter2"));
+}
+
+#[tokio::test]
+async fn test_get_optional() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/users/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(TestData {
+            id: 1,
+            name: "Alice".to_string(),
+        }))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/users/2"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/users/3"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let user = client.get_optional::<TestData>("/users/1").await.unwrap();
+    assert_eq!(user.unwrap().data.name, "Alice");
+
+    let user = client.get_optional::<TestData>("/users/2").await.unwrap();
+    assert!(user.is_none());
+
+    let error = client
+        .get_optional::<TestData>("/users/3")
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(http::StatusCode::FORBIDDEN));
+}
+
+#[tokio::test]
+async fn test_accepted_status_is_not_retried() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(ResponseTemplate::new(503).set_body_json(TestData {
+            id: 0,
+            name: "maintenance".to_string(),
+        }))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 3,
+        })
+        .build()
+        .unwrap();
+
+    let metadata =
+        calleen::metadata::RequestMetadata::new(http::Method::GET, "/test").accept_status(503);
+    let response = client.call::<(), TestData>(metadata, None).await.unwrap();
+
+    assert_eq!(response.status, 503);
+    assert_eq!(response.data.name, "maintenance");
+    assert_eq!(response.attempts, 1);
+}
+
+#[tokio::test]
+async fn test_call_either() {
+    #[derive(Debug, Deserialize)]
+    struct Declined {
+        reason: String,
+    }
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/ok"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(TestData {
+            id: 1,
+            name: "receipt".to_string(),
+        }))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/declined"))
+        .respond_with(
+            ResponseTemplate::new(402).set_body_string(r#"{"reason":"insufficient funds"}"#),
+        )
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/error"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let body = serde_json::json!({});
+    let call = |path: &'static str| {
+        let metadata =
+            calleen::metadata::RequestMetadata::new(http::Method::POST, path).accept_status(402);
+        client.call_either::<_, TestData, Declined>(metadata, Some(&body))
+    };
+
+    match call("/ok").await.unwrap().data {
+        Either::Left(data) => assert_eq!(data.name, "receipt"),
+        Either::Right(declined) => panic!("Expected Left, got {:?}", declined),
+    }
+
+    let response = call("/declined").await.unwrap();
+    assert_eq!(response.status, 402);
+    match response.data {
+        Either::Right(declined) => assert_eq!(declined.reason, "insufficient funds"),
+        Either::Left(data) => panic!("Expected Right, got {:?}", data),
+    }
+
+    let error = call("/error").await.unwrap_err();
+    assert!(matches!(error, Error::HttpError { .. }));
+}
+
+#[tokio::test]
+async fn test_empty_body_responses() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/jobs"))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("HEAD"))
+        .and(path("/files/report"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-file-size", "1024"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let response = client
+        .post::<_, Option<TestData>>("/jobs", &serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(response.status, 202);
+    assert!(response.data.is_none());
+
+    // Types that can't be null still fail to deserialize
+    let error = client
+        .post::<_, TestData>("/jobs", &serde_json::json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::DeserializationFailed { .. }));
+
+    let response = client.head("/files/report").await.unwrap();
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("x-file-size"), Some("1024"));
+}
+
+#[tokio::test]
+async fn test_paginate_link_header() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[{"id":3,"name":"c"}]"#))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header(
+                    "link",
+                    format!(
+                        r#"<{}/items?page=2>; rel="next", <{}/items?page=2>; rel="last""#,
+                        mock_server.uri(),
+                        mock_server.uri()
+                    ),
+                )
+                .set_body_string(r#"[{"id":1,"name":"a"},{"id":2,"name":"b"}]"#),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let items: Vec<TestData> = client
+        .paginate(
+            calleen::metadata::RequestMetadata::new(http::Method::GET, "/items"),
+            PaginationConfig::default(),
+        )
+        .map(|item| item.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(
+        items.iter().map(|item| item.id).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+}
+
+#[tokio::test]
+async fn test_paginate_cursor_with_retries() {
+    let mock_server = MockServer::start().await;
+
+    let attempt_count = Arc::new(AtomicUsize::new(0));
+    let attempt_count_clone = attempt_count.clone();
+
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .and(query_param("cursor", "abc"))
+        .respond_with(move |_req: &wiremock::Request| {
+            if attempt_count_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                ResponseTemplate::new(503)
+            } else {
+                ResponseTemplate::new(200).set_body_string(
+                    r#"{"data":[{"id":2,"name":"b"}],"meta":{"next_cursor":null}}"#,
+                )
+            }
+        })
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(r#"{"data":[{"id":1,"name":"a"}],"meta":{"next_cursor":"abc"}}"#),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 2,
+        })
+        .build()
+        .unwrap();
+
+    let config = PaginationConfig::builder()
+        .scheme(PageScheme::Cursor {
+            cursor: "/meta/next_cursor".to_string(),
+            param: "cursor".to_string(),
+        })
+        .items("/data")
+        .build();
+
+    let items: Vec<TestData> = client
+        .paginate(
+            calleen::metadata::RequestMetadata::new(http::Method::GET, "/events"),
+            config,
+        )
+        .map(|item| item.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[1].name, "b");
+    assert_eq!(attempt_count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_paginate_page_number_max_pages() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/pages"))
+        .respond_with(|req: &wiremock::Request| {
+            let page = req
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == "page")
+                .unwrap()
+                .1
+                .to_string();
+            ResponseTemplate::new(200)
+                .set_body_string(format!(r#"[{{"id":{},"name":"item"}}]"#, page))
+        })
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let config = PaginationConfig::builder()
+        .scheme(PageScheme::PageNumber {
+            param: "page".to_string(),
+            first_page: 1,
+        })
+        .max_pages(3)
+        .build();
+
+    let items: Vec<TestData> = client
+        .paginate(
+            calleen::metadata::RequestMetadata::new(http::Method::GET, "/pages"),
+            config,
+        )
+        .map(|item| item.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(
+        items.iter().map(|item| item.id).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_paginate_stops_on_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"unexpected":true}"#))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let results: Vec<calleen::Result<TestData>> = client
+        .paginate(
+            calleen::metadata::RequestMetadata::new(http::Method::GET, "/items"),
+            PaginationConfig::default(),
+        )
+        .collect()
+        .await;
+
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+        results[0],
+        Err(Error::DeserializationFailed { .. })
+    ));
+}
+
+fn batch_request(path: String) -> (calleen::metadata::RequestMetadata, Option<()>) {
+    (
+        calleen::metadata::RequestMetadata::new(http::Method::GET, path),
+        None,
+    )
+}
+
+#[tokio::test]
+async fn test_call_many_bounded_and_ordered() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(|req: &wiremock::Request| {
+            let id: u32 = req
+                .url
+                .path()
+                .trim_start_matches("/items/")
+                .parse()
+                .unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(TestData {
+                    id,
+                    name: "item".to_string(),
+                })
+                // Later items respond faster, so completion order differs from input order
+                .set_delay(Duration::from_millis(150 - u64::from(id) * 10))
+        })
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let results: Vec<_> = client
+        .call_many::<_, TestData, _>((0..9).map(|id| batch_request(format!("/items/{}", id))), 3)
+        .collect()
+        .await;
+
+    // 9 requests, 3 at a time, each taking at least 70ms
+    assert!(start.elapsed() >= Duration::from_millis(210));
+    assert_eq!(
+        results
+            .into_iter()
+            .map(|result| result.unwrap().data.id)
+            .collect::<Vec<_>>(),
+        (0..9).collect::<Vec<_>>()
+    );
+
+    let mut completed: Vec<_> = client
+        .call_many_unordered::<_, TestData, _>(
+            (0..9).map(|id| batch_request(format!("/items/{}", id))),
+            9,
+        )
+        .map(|(index, result)| (index, result.unwrap().data.id))
+        .collect()
+        .await;
+    assert!(completed.iter().all(|(index, id)| *index == *id as usize));
+    assert_ne!(completed.first().unwrap().0, 0);
+    completed.sort();
+    assert_eq!(completed.len(), 9);
+}
+
+#[tokio::test]
+async fn test_try_call_many_stops_on_first_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/items/1"))
+        .respond_with(ResponseTemplate::new(400))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(TestData {
+            id: 0,
+            name: "item".to_string(),
+        }))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let error = client
+        .try_call_many::<_, TestData, _>(
+            (0..20).map(|id| batch_request(format!("/items/{}", id))),
+            2,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(http::StatusCode::BAD_REQUEST));
+    assert!(mock_server.received_requests().await.unwrap().len() < 20);
+
+    let responses = client
+        .try_call_many::<_, TestData, _>(
+            [0, 2, 3].map(|id| batch_request(format!("/items/{}", id))),
+            2,
+        )
+        .await
+        .unwrap();
+    assert_eq!(responses.len(), 3);
+}
+
+#[tokio::test]
+async fn test_call_many_pauses_batch_when_rate_limited() {
+    let mock_server = MockServer::start().await;
+    let start = std::time::Instant::now();
+
+    let limited = Arc::new(AtomicUsize::new(0));
+    let limited_clone = limited.clone();
+    Mock::given(method("GET"))
+        .and(path("/limited"))
+        .respond_with(move |_req: &wiremock::Request| {
+            if limited_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                ResponseTemplate::new(429).insert_header("retry-after", "1")
+            } else {
+                ResponseTemplate::new(200).set_body_string("null")
+            }
+        })
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("null")
+                .set_delay(Duration::from_millis(200)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let later_requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let later_requests_clone = later_requests.clone();
+    Mock::given(method("GET"))
+        .and(path("/later"))
+        .respond_with(move |_req: &wiremock::Request| {
+            later_requests_clone.lock().unwrap().push(start.elapsed());
+            ResponseTemplate::new(200).set_body_string("null")
+        })
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 2,
+        })
+        .build()
+        .unwrap();
+
+    let requests = ["/limited", "/slow", "/later", "/later"].map(|p| batch_request(p.to_string()));
+    let results: Vec<_> = client.call_many::<_, (), _>(requests, 2).collect().await;
+
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert_eq!(limited.load(Ordering::SeqCst), 2);
+
+    // Requests started after the 429 waited for the rate limit to reset
+    let later_requests = later_requests.lock().unwrap();
+    assert_eq!(later_requests.len(), 2);
+    assert!(later_requests
+        .iter()
+        .all(|elapsed| *elapsed >= Duration::from_millis(900)));
+}
+
+#[tokio::test]
+async fn test_rate_limit_shared_across_requests() {
+    let mock_server = MockServer::start().await;
+    let start = std::time::Instant::now();
+
+    let limited = Arc::new(AtomicUsize::new(0));
+    let limited_clone = limited.clone();
+    Mock::given(method("GET"))
+        .and(path("/limited"))
+        .respond_with(move |_req: &wiremock::Request| {
+            if limited_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                ResponseTemplate::new(429).insert_header("retry-after", "1")
+            } else {
+                ResponseTemplate::new(200).set_body_string("null")
+            }
+        })
+        .mount(&mock_server)
+        .await;
+
+    let other_requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let other_requests_clone = other_requests.clone();
+    Mock::given(method("GET"))
+        .and(path("/other"))
+        .respond_with(move |_req: &wiremock::Request| {
+            other_requests_clone.lock().unwrap().push(start.elapsed());
+            ResponseTemplate::new(200).set_body_string("null")
+        })
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 2,
+        })
+        .build()
+        .unwrap();
+
+    let limited_call = {
+        let client = client.clone();
+        tokio::spawn(async move { client.get::<()>("/limited").await })
+    };
+
+    // Wait for the 429 to block the host
+    let bucket = mock_server.address().to_string();
+    while client.rate_limited_until(&bucket).is_none() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert!(client.rate_limited_buckets().contains_key(&bucket));
+
+    client.get::<()>("/other").await.unwrap();
+    limited_call.await.unwrap().unwrap();
+
+    assert!(other_requests.lock().unwrap()[0] >= Duration::from_millis(900));
+    assert!(client.rate_limited_until(&bucket).is_none());
+}
+
+#[tokio::test]
+async fn test_rate_limit_buckets_are_separate() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "30"))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/users"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let metadata = calleen::metadata::RequestMetadata::new(http::Method::GET, "/search")
+        .with_rate_limit_bucket("search");
+    client.call::<(), ()>(metadata, None).await.unwrap_err();
+
+    assert!(client.rate_limited_until("search").is_some());
+    assert!(client
+        .rate_limited_until(&mock_server.address().to_string())
+        .is_none());
+
+    // Requests in other buckets aren't blocked
+    let start = std::time::Instant::now();
+    client.get::<()>("/users").await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+/// Mounts a mock that responds 429 with `retry_after` on the first request and
+/// 200 afterwards, returning the request counter.
+async fn mount_rate_limited_once(
+    mock_server: &MockServer,
+    retry_after: &'static str,
+) -> Arc<AtomicUsize> {
+    let count = Arc::new(AtomicUsize::new(0));
+    let count_clone = count.clone();
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(move |_req: &wiremock::Request| {
+            if count_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                ResponseTemplate::new(429).insert_header("retry-after", retry_after)
+            } else {
+                ResponseTemplate::new(200).set_body_string("null")
+            }
+        })
+        .mount(mock_server)
+        .await;
+    count
+}
+
+#[tokio::test]
+async fn test_rate_limit_ignores_retry_after_when_disabled() {
+    let mock_server = MockServer::start().await;
+    let count = mount_rate_limited_once(&mock_server, "5").await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 2,
+        })
+        .rate_limit_config(
+            RateLimitConfig::builder()
+                .respect_retry_after(false)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    client.get::<()>("/test").await.unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_rate_limit_info_keeps_ignored_retry_after() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "5"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .rate_limit_config(
+            RateLimitConfig::builder()
+                .respect_retry_after(false)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let Error::MaxRetriesExceeded { last_error, .. } = client.get::<()>("/test").await.unwrap_err()
+    else {
+        panic!("expected max retries exceeded");
+    };
+    let info = last_error
+        .rate_limit_info()
+        .expect("429 carries rate limit info");
+    assert_eq!(info.retry_after, Some(Duration::from_secs(5)));
+    // The ignored Retry-After doesn't pause later requests
+    assert!(client
+        .rate_limited_until(&mock_server.address().to_string())
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_rate_limit_fails_when_wait_exceeds_max() {
+    let mock_server = MockServer::start().await;
+    let count = mount_rate_limited_once(&mock_server, "600").await;
+
+    let recorder = EventRecorder::default();
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 3,
+        })
+        .metrics_recorder(Box::new(recorder.clone()))
+        .rate_limit_config(
+            RateLimitConfig::builder()
+                .max_wait(Duration::from_secs(1))
+                .max_wait_policy(MaxWaitPolicy::Fail)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    match client.get::<()>("/test").await.unwrap_err() {
+        Error::RateLimited {
+            retry_after,
+            request,
+        } => {
+            assert!(retry_after > Duration::from_secs(590));
+            assert_eq!(
+                request.expect("429 carries its request").url.path(),
+                "/test"
+            );
+        }
+        e => panic!("Expected RateLimited, got {:?}", e),
+    }
+
+    // Later requests to the same host fail without being sent
+    let error = client.get::<()>("/test").await.unwrap_err();
+    assert!(matches!(error, Error::RateLimited { .. }));
+    assert!(error.request().is_none());
+    assert!(!error.is_retryable());
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    // The request that wasn't sent is still recorded as an attempt
+    assert_eq!(
+        recorder.events(),
+        vec![
+            "attempt /test 429",
+            "call GET /test none",
+            "attempt /test none",
+            "call GET /test none",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limit_huge_retry_after_fails_without_panicking() {
+    let mock_server = MockServer::start().await;
+    let count = mount_rate_limited_once(&mock_server, "10000000000000000000").await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .rate_limit_config(
+            RateLimitConfig::builder()
+                .max_wait(Duration::from_secs(1))
+                .max_wait_policy(MaxWaitPolicy::Fail)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let error = client.get::<()>("/test").await.unwrap_err();
+    assert!(matches!(error, Error::RateLimited { .. }));
+    assert!(client
+        .rate_limited_until(&mock_server.address().to_string())
+        .is_some());
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_waits_past_max_when_configured() {
+    let mock_server = MockServer::start().await;
+    let count = mount_rate_limited_once(&mock_server, "1").await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 2,
+        })
+        .rate_limit_config(
+            RateLimitConfig::builder()
+                .max_wait(Duration::from_millis(100))
+                .max_wait_policy(MaxWaitPolicy::Wait)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    client.get::<()>("/test").await.unwrap();
+
+    assert!(start.elapsed() >= Duration::from_millis(900));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+/// Answers every request in-process with the request's method and body.
+struct EchoTransport;
+
+impl Transport for EchoTransport {
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "method": request.method().as_str(),
+                "uri": request.uri().to_string(),
+                "body": serde_json::from_slice::<serde_json::Value>(request.body()).unwrap(),
+            });
+            Ok(http::Response::new(body.to_string().into_bytes()))
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_custom_transport() {
+    let client = Client::builder()
+        .base_url("http://service.local")
+        .unwrap()
+        .transport(Box::new(EchoTransport))
+        .build()
+        .unwrap();
+
+    let response = client
+        .post::<_, serde_json::Value>("/echo", &serde_json::json!({"id": 1}))
+        .await
+        .unwrap();
+
+    assert_eq!(response.data["method"], "POST");
+    assert_eq!(response.data["uri"], "http://service.local/echo");
+    assert_eq!(response.data["body"]["id"], 1);
+}
+
+#[tokio::test]
+async fn test_connection_refused_is_network_error() {
+    // Bind and drop a listener to find a port nothing listens on
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let client = Client::builder()
+        .base_url(format!("http://127.0.0.1:{}", port))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let result = client.get::<()>("/test").await;
+
+    let Err(Error::MaxRetriesExceeded { last_error, .. }) = result else {
+        panic!("expected max retries exceeded, got {:?}", result);
+    };
+    match *last_error {
+        Error::Network(e) => {
+            assert_eq!(e.kind(), NetworkErrorKind::Connect);
+            assert!(std::error::Error::source(&e).is_some());
+        }
+        other => panic!("expected a network error, got {:?}", other),
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unix_socket() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let socket = std::env::temp_dir().join(format!("calleen-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&socket);
+    let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+
+    // Answer a single request with its Host header and path
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let request = String::from_utf8(request).unwrap();
+        let path = request.split_whitespace().nth(1).unwrap().to_string();
+        let host = request
+            .lines()
+            .find_map(|line| line.strip_prefix("host: "))
+            .unwrap()
+            .to_string();
+
+        let body = serde_json::json!({"path": path, "host": host}).to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let client = Client::builder()
+        .base_url("http://docker.local")
+        .unwrap()
+        .unix_socket(&socket)
+        .build()
+        .unwrap();
+
+    let response = client.get::<serde_json::Value>("/version").await.unwrap();
+    server.await.unwrap();
+    std::fs::remove_file(&socket).unwrap();
+
+    assert_eq!(response.data["path"], "/version");
+    assert_eq!(response.data["host"], "docker.local");
+}
+
+#[tokio::test]
+async fn test_connection_config_user_agent_and_redirects() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/old"))
+        .and(header("user-agent", "calleen-test/1.0"))
+        .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .connection_config(
+            ConnectionConfig::builder()
+                .user_agent("calleen-test/1.0")
+                .max_redirects(0)
+                .tcp_nodelay(true)
+                .pool_max_idle_per_host(1)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    // With redirects disabled, the redirect is returned as an error
+    let result = client.get::<()>("/old").await;
+    assert_eq!(result.unwrap_err().status().unwrap().as_u16(), 301);
+}
+
+#[tokio::test]
+async fn test_connection_config_proxy() {
+    let proxy = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"proxied": true})),
+        )
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    let client = Client::builder()
+        .base_url("http://api.example.invalid")
+        .unwrap()
+        .connection_config(
+            ConnectionConfig::builder()
+                .no_system_proxy(true)
+                .proxy(Proxy::http(proxy.uri()))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let response = client.get::<serde_json::Value>("/test").await.unwrap();
+    assert_eq!(response.data["proxied"], true);
+}
+
+#[test]
+fn test_connection_config_rejects_invalid_options() {
+    let result = Client::builder()
+        .base_url("http://api.example.com")
+        .unwrap()
+        .connection_config(
+            ConnectionConfig::builder()
+                .proxy(Proxy::all("not a url"))
+                .build(),
+        )
+        .build();
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+
+    let result = Client::builder()
+        .base_url("http://api.example.com")
+        .unwrap()
+        .reqwest_builder(reqwest::Client::builder())
+        .transport(Box::new(EchoTransport))
+        .build();
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+
+    let result = Client::builder()
+        .base_url("http://api.example.com")
+        .unwrap()
+        .resolve("api.example.com", "127.0.0.1:0".parse().unwrap())
+        .transport(Box::new(EchoTransport))
+        .build();
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+}
+
+/// Starts a server answering every request with `status` and its own name.
+async fn named_server(name: &str, status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(status).set_body_json(name))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_round_robin_across_base_urls() {
+    let first = named_server("first", 200).await;
+    let second = named_server("second", 200).await;
+
+    let client = Client::builder()
+        .base_urls([first.uri(), second.uri()])
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let mut names = Vec::new();
+    for _ in 0..4 {
+        names.push(client.get::<String>("/test").await.unwrap().data);
+    }
+    assert_eq!(names, vec!["first", "second", "first", "second"]);
+}
+
+#[tokio::test]
+async fn test_retry_fails_over_to_another_endpoint() {
+    let failing = named_server("failing", 503).await;
+    let healthy = named_server("healthy", 200).await;
+
+    let client = Client::builder()
+        .base_urls([failing.uri(), healthy.uri()])
+        .unwrap()
+        .balance_config(
+            BalanceConfig::builder()
+                .strategy(BalanceStrategy::Random)
+                .failure_threshold(0)
+                .build(),
+        )
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(1),
+            max_retries: 1,
+        })
+        .build()
+        .unwrap();
+
+    // A single retry always reaches the healthy endpoint
+    for _ in 0..10 {
+        let response = client.get::<String>("/test").await.unwrap();
+        assert_eq!(response.data, "healthy");
+    }
+}
+
+#[tokio::test]
+async fn test_unreachable_endpoint_is_ejected() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let healthy = named_server("healthy", 200).await;
+
+    let client = Client::builder()
+        .base_urls([format!("http://127.0.0.1:{}", port), healthy.uri()])
+        .unwrap()
+        .balance_config(
+            BalanceConfig::builder()
+                .failure_threshold(1)
+                .ejection_duration(Duration::from_secs(60))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    assert!(client.get::<String>("/test").await.is_err());
+    for _ in 0..4 {
+        assert_eq!(client.get::<String>("/test").await.unwrap().data, "healthy");
+    }
+}
+
+#[test]
+fn test_base_urls_required() {
+    let result = Client::builder()
+        .base_urls(Vec::<String>::new())
+        .unwrap()
+        .build();
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+
+    let result = Client::builder().base_urls(["http://valid.local", "not a url"]);
+    assert!(matches!(result, Err(Error::InvalidUrl(_))));
+}
+
+#[tokio::test]
+async fn test_resolve_overrides() {
+    let first = named_server("first", 200).await;
+    let second = named_server("second", 200).await;
+    let port = |server: &MockServer| server.address().port();
+
+    let client = Client::builder()
+        .base_urls([
+            format!("http://first.service.test:{}", port(&first)),
+            format!("http://second.service.test:{}", port(&second)),
+        ])
+        .unwrap()
+        .resolve("first.service.test", "127.0.0.1:0".parse().unwrap())
+        .resolve("SECOND.service.test", "127.0.0.1:0".parse().unwrap())
+        .build()
+        .unwrap();
+
+    assert_eq!(client.get::<String>("/test").await.unwrap().data, "first");
+    assert_eq!(client.get::<String>("/test").await.unwrap().data, "second");
+}
+
+/// Resolves every host to localhost, failing for hosts ending in `.missing`.
+struct LocalResolver {
+    lookups: Arc<AtomicUsize>,
+}
+
+impl Resolve for LocalResolver {
+    fn resolve(&self, host: &str) -> Resolving {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        let missing = host.ends_with(".missing");
+        Box::pin(async move {
+            if missing {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "no such host",
+                ));
+            }
+            Ok(vec!["127.0.0.1:0".parse().unwrap()])
+        })
+    }
+}
+
+/// Asserts that a request failed because its host couldn't be resolved.
+fn assert_dns_error(result: Result<calleen::Response<String>, Error>) {
+    let Err(Error::MaxRetriesExceeded { last_error, .. }) = result else {
+        panic!("expected max retries exceeded, got {:?}", result);
+    };
+    match *last_error {
+        Error::Network(e) => {
+            assert_eq!(e.kind(), NetworkErrorKind::Dns);
+            assert!(e.is_dns());
+        }
+        other => panic!("expected a network error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_custom_dns_resolver() {
+    let server = named_server("local", 200).await;
+    let lookups = Arc::new(AtomicUsize::new(0));
+
+    let client = Client::builder()
+        .base_url(format!(
+            "http://api.service.test:{}",
+            server.address().port()
+        ))
+        .unwrap()
+        .dns_resolver(Box::new(LocalResolver {
+            lookups: lookups.clone(),
+        }))
+        .build()
+        .unwrap();
+    assert_eq!(client.get::<String>("/test").await.unwrap().data, "local");
+    assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+    let client = Client::builder()
+        .base_url("http://api.service.missing")
+        .unwrap()
+        .dns_resolver(Box::new(LocalResolver { lookups }))
+        .build()
+        .unwrap();
+    assert_dns_error(client.get::<String>("/test").await);
+}
+
+#[tokio::test]
+async fn test_system_dns_error() {
+    // The .invalid top-level domain never resolves
+    let client = Client::builder()
+        .base_url("http://calleen.invalid")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_dns_error(client.get::<String>("/test").await);
+}
+
+#[tokio::test]
+async fn test_request_signer_signs_every_attempt() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json("ok"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(1),
+            max_retries: 1,
+        })
+        .request_signer(Box::new(
+            HmacSigner::new(b"secret".to_vec()).timestamp_header("X-Timestamp"),
+        ))
+        .build()
+        .unwrap();
+
+    client.get::<String>("/test").await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"secret");
+    for request in requests {
+        let timestamp = request.headers["x-timestamp"].to_str().unwrap();
+        let signed = format!(
+            "GET\n/test\n\n{}\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            timestamp
+        );
+        let signature = request.headers["x-signature"].to_str().unwrap();
+        let signature = (0..signature.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&signature[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        assert!(ring::hmac::verify(&key, signed.as_bytes(), &signature).is_ok());
+    }
+}
+
+/// A signer that refuses to sign anything.
+struct FailingSigner;
+
+impl RequestSigner for FailingSigner {
+    fn sign(&self, _request: &mut http::Request<Vec<u8>>) -> Result<(), Error> {
+        Err(Error::ConfigurationError("credentials expired".to_string()))
+    }
+}
+
+#[tokio::test]
+async fn test_request_signer_error_is_returned() {
+    let mock_server = MockServer::start().await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .request_signer(Box::new(FailingSigner))
+        .build()
+        .unwrap();
+
+    let result = client.get::<String>("/test").await;
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cookie_jar_persists_sessions() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(204)
+                .append_header("set-cookie", "session=abc; Path=/; HttpOnly")
+                .append_header("set-cookie", "theme=dark; Path=/settings"),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/profile"))
+        .and(header("cookie", "session=abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json("alice"))
+        .mount(&mock_server)
+        .await;
+
+    let file = std::env::temp_dir().join(format!(
+        "calleen-integration-cookies-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&file);
+    let build = |jar| {
+        Client::builder()
+            .base_url(mock_server.uri())
+            .unwrap()
+            .cookie_jar(jar)
+            .build()
+            .unwrap()
+    };
+
+    let client = build(CookieJar::persistent(&file).unwrap());
+    client
+        .post::<_, ()>("/login", &serde_json::json!({"user": "alice"}))
+        .await
+        .unwrap();
+    let response = client.clone().get::<String>("/profile").await.unwrap();
+    assert_eq!(response.data, "alice");
+
+    // A new client, as after a restart, picks the session up from the file
+    let restarted = build(CookieJar::persistent(&file).unwrap());
+    let response = restarted.get::<String>("/profile").await.unwrap();
+    assert_eq!(response.data, "alice");
+
+    // Without the jar, there's no session
+    let anonymous = build(CookieJar::new());
+    assert!(anonymous.get::<String>("/profile").await.is_err());
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[tokio::test]
+async fn test_redirects_are_recorded() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/old"))
+        .respond_with(ResponseTemplate::new(308).insert_header("location", "/submit"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/submit"))
+        .respond_with(ResponseTemplate::new(303).insert_header("location", "/result?id=1"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/result"))
+        .and(query_param("id", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"id": 1})))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let response = client
+        .post::<_, serde_json::Value>("/old", &serde_json::json!({"name": "test"}))
+        .await
+        .unwrap();
+    assert_eq!(response.data["id"], 1);
+    assert_eq!(
+        response.final_url.as_ref().unwrap().as_str(),
+        format!("{}/result?id=1", mock_server.uri())
+    );
+    let redirects = response
+        .redirects
+        .iter()
+        .map(|url| url.path())
+        .collect::<Vec<_>>();
+    assert_eq!(redirects, vec!["/old", "/submit"]);
+    let request = response.request.unwrap();
+    assert_eq!(
+        request.url.as_str(),
+        format!("{}/result?id=REDACTED", mock_server.uri())
+    );
+    assert_eq!(request.redirects, response.redirects);
+
+    // The 308 kept the body, and the 303 dropped it
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests[1].body, requests[0].body);
+    assert!(requests[2].body.is_empty());
+    assert!(!requests[2].headers.contains_key("content-type"));
+}
+
+#[tokio::test]
+async fn test_cross_origin_redirect_strips_credentials() {
+    let origin = MockServer::start().await;
+    let other = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/download"))
+        .respond_with(
+            ResponseTemplate::new(307)
+                .insert_header("location", format!("{}/files/1", other.uri())),
+        )
+        .mount(&origin)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/files/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("\"contents\""))
+        .mount(&other)
+        .await;
+
+    let build = |policy| {
+        Client::builder()
+            .base_url(origin.uri())
+            .unwrap()
+            .default_header("authorization", "Bearer secret")
+            .unwrap()
+            .redirect_policy(policy)
+            .build()
+            .unwrap()
+    };
+
+    // By default, the other origin doesn't get the credentials
+    let response = build(RedirectPolicy::default())
+        .get::<String>("/download")
+        .await
+        .unwrap();
+    assert_eq!(response.data, "contents");
+    assert_eq!(response.redirects.len(), 1);
+    let requests = other.received_requests().await.unwrap();
+    assert!(!requests[0].headers.contains_key("authorization"));
+
+    let follow = RedirectPolicy::builder()
+        .cross_origin(CrossOriginRedirects::Follow)
+        .build();
+    build(follow).get::<String>("/download").await.unwrap();
+    let requests = other.received_requests().await.unwrap();
+    assert_eq!(requests[1].headers["authorization"], "Bearer secret");
+
+    // Refused redirects are returned as errors
+    let refuse = RedirectPolicy::builder()
+        .cross_origin(CrossOriginRedirects::Refuse)
+        .build();
+    let error = build(refuse).get::<String>("/download").await.unwrap_err();
+    assert_eq!(error.status().unwrap().as_u16(), 307);
+    assert_eq!(
+        error.final_url().unwrap().as_str(),
+        format!("{}/download", origin.uri())
+    );
+    assert!(error.redirects().is_empty());
+    assert_eq!(other.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_cross_origin_redirect_is_not_signed() {
+    let origin = MockServer::start().await;
+    let other = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/download"))
+        .respond_with(
+            ResponseTemplate::new(307)
+                .insert_header("location", format!("{}/download", other.uri())),
+        )
+        .mount(&origin)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/download"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("\"contents\""))
+        .mount(&other)
+        .await;
+
+    let build = |policy| {
+        Client::builder()
+            .base_url(origin.uri())
+            .unwrap()
+            .request_signer(Box::new(HmacSigner::new(b"secret".to_vec())))
+            .redirect_policy(policy)
+            .build()
+            .unwrap()
+    };
+
+    // The other origin would otherwise get a signature it could replay
+    build(RedirectPolicy::default())
+        .get::<String>("/download")
+        .await
+        .unwrap();
+    let requests = origin.received_requests().await.unwrap();
+    assert!(requests[0].headers.contains_key("x-signature"));
+    let requests = other.received_requests().await.unwrap();
+    assert!(!requests[0].headers.contains_key("x-signature"));
+
+    let follow = RedirectPolicy::builder()
+        .cross_origin(CrossOriginRedirects::Follow)
+        .build();
+    build(follow).get::<String>("/download").await.unwrap();
+    let requests = other.received_requests().await.unwrap();
+    assert!(requests[1].headers.contains_key("x-signature"));
+}
+
+#[tokio::test]
+async fn test_max_redirects() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/loop"))
+        .respond_with(ResponseTemplate::new(302).insert_header("location", "/loop"))
+        .expect(4)
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .redirect_policy(RedirectPolicy::builder().max_redirects(3).build())
+        .build()
+        .unwrap();
+
+    let error = client.get::<()>("/loop").await.unwrap_err();
+    assert_eq!(error.status().unwrap().as_u16(), 302);
+    assert_eq!(error.redirects().len(), 3);
+
+    // max_redirects is either set on the connection or in the policy
+    let result = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .connection_config(ConnectionConfig::builder().max_redirects(3).build())
+        .redirect_policy(RedirectPolicy::none())
+        .build();
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+}
+
+#[tokio::test]
+async fn test_request_summary() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/users"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({"id": 1})))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .default_header("authorization", "Bearer secret-token")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let body = serde_json::json!({"name": "alice"});
+    let response = client
+        .post::<_, serde_json::Value>("/users", &body)
+        .await
+        .unwrap();
+    let request = response.request.unwrap();
+    assert_eq!(request.method, http::Method::POST);
+    assert_eq!(request.url.as_str(), format!("{}/users", mock_server.uri()));
+    assert_eq!(request.body_size, serde_json::to_vec(&body).unwrap().len());
+    assert_eq!(request.headers["content-type"], "application/json");
+    assert_ne!(request.headers["authorization"], "Bearer secret-token");
+
+    let error = client.get::<()>("/missing").await.unwrap_err();
+    let request = error.request().unwrap();
+    assert_eq!(request.method, http::Method::GET);
+    assert_eq!(request.url.path(), "/missing");
+    assert_eq!(request.body_size, 0);
+
+    // Query parameter values are redacted, since they often carry credentials,
+    // but a successful response still has the URL it came from
+    let mut metadata = calleen::metadata::RequestMetadata::new(http::Method::GET, "/missing");
+    metadata
+        .query_params
+        .insert("token".to_string(), "secret".to_string());
+    let error = client.call::<(), ()>(metadata, None).await.unwrap_err();
+    assert_eq!(error.request().unwrap().url.query(), Some("token=REDACTED"));
+    assert_eq!(error.final_url().unwrap().query(), Some("token=REDACTED"));
+
+    let mut metadata = calleen::metadata::RequestMetadata::new(http::Method::POST, "/users");
+    metadata
+        .query_params
+        .insert("token".to_string(), "secret".to_string());
+    let response = client
+        .call::<_, serde_json::Value>(metadata, Some(&body))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.request.unwrap().url.query(),
+        Some("token=REDACTED")
+    );
+    assert_eq!(response.final_url.unwrap().query(), Some("token=secret"));
+
+    // Connection failures carry the request too
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let client = Client::builder()
+        .base_url(format!("http://127.0.0.1:{}", port))
+        .unwrap()
+        .build()
+        .unwrap();
+    let error = client.get::<()>("/offline").await.unwrap_err();
+    assert_eq!(error.request().unwrap().url.path(), "/offline");
+}
+
+#[tokio::test]
+async fn test_attempt_timings() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"id": 1, "name": "slow"}))
+                .set_delay(Duration::from_millis(100)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(50),
+            max_retries: 2,
+        })
+        .build()
+        .unwrap();
+
+    let response = client.get::<TestData>("/slow").await.unwrap();
+    let history = &response.attempt_history;
+    assert_eq!(history.len(), 2);
+
+    assert_eq!(history[0].number, 1);
+    assert_eq!(history[0].status.unwrap().as_u16(), 503);
+    assert!(history[0].error.as_ref().unwrap().contains("503"));
+    assert_eq!(history[0].timings.waited, Duration::ZERO);
+
+    // The retry delay is kept apart from the server's latency
+    assert_eq!(history[1].number, 2);
+    assert!(history[1].error.is_none());
+    assert_eq!(history[1].timings, response.timings);
+    assert!(response.timings.waited >= Duration::from_millis(50));
+    assert!(response.timings.headers >= Duration::from_millis(100));
+    assert!(response.timings.total >= response.timings.server());
+    assert!(response.timings.total < response.latency);
+    assert_eq!(response.waited(), response.timings.waited);
+}
@@ -0,0 +1,61 @@
+//! Integration tests for the `metrics` facade recorder.
+
+#![cfg(feature = "metrics")]
+
+use calleen::metrics::FacadeRecorder;
+use calleen::Client;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_facade_recorder() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .metrics_recorder(Box::new(FacadeRecorder))
+        .build()
+        .unwrap();
+
+    let _ = client.get::<serde_json::Value>("/test").await;
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let (key, _, _, value) = snapshot
+        .iter()
+        .find(|(key, ..)| key.key().name() == "calleen_requests_total")
+        .expect("request counter");
+    assert_eq!(value, &DebugValue::Counter(1));
+
+    let labels: Vec<_> = key
+        .key()
+        .labels()
+        .map(|label| (label.key(), label.value()))
+        .collect();
+    assert_eq!(
+        labels,
+        vec![("method", "GET"), ("endpoint", "/test"), ("status", "404")]
+    );
+
+    for name in [
+        "calleen_request_duration_seconds",
+        "calleen_attempt_duration_seconds",
+    ] {
+        assert!(
+            snapshot.iter().any(|(key, ..)| key.key().name() == name),
+            "missing {}",
+            name
+        );
+    }
+}
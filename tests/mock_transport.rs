@@ -0,0 +1,277 @@
+//! Tests for the mock transport.
+
+#![cfg(feature = "test-util")]
+
+use calleen::hedge::{HedgeConfig, HedgeDelay};
+use calleen::metadata::RequestMetadata;
+use calleen::mock::{Mock, MockResponse, MockTransport};
+use calleen::{Client, Error, NetworkErrorKind, RetryStrategy};
+use http::Method;
+use serde_json::json;
+use std::time::Duration;
+
+fn client(transport: &MockTransport) -> Client {
+    Client::builder()
+        .base_url("https://api.example.com")
+        .unwrap()
+        .mock_transport(transport.clone())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_mock_retries_scripted_failures() {
+    let transport = MockTransport::new();
+    transport.register(
+        Mock::new(Method::GET, "/users/1")
+            .respond_with(MockResponse::network_error(
+                NetworkErrorKind::Request,
+                "connection reset",
+            ))
+            .respond_with(MockResponse::new(503))
+            .respond_with(MockResponse::json(200, &json!({"id": 1})))
+            .expect(3),
+    );
+
+    // The hour of backoff passes instantly on the paused clock
+    let client = Client::builder()
+        .base_url("https://api.example.com")
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_secs(30 * 60),
+            max_retries: 3,
+        })
+        .mock_transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let start = tokio::time::Instant::now();
+    let response = client.get::<serde_json::Value>("/users/1").await.unwrap();
+
+    assert_eq!(response.data["id"], 1);
+    assert_eq!(response.attempts, 3);
+    assert_eq!(start.elapsed(), Duration::from_secs(60 * 60));
+    transport.verify();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_mock_network_error() {
+    let transport = MockTransport::new();
+    transport.register(Mock::new(Method::GET, "/users/1").respond_with(
+        MockResponse::network_error(NetworkErrorKind::Connect, "connection refused"),
+    ));
+
+    let result = client(&transport)
+        .get::<serde_json::Value>("/users/1")
+        .await;
+
+    let Err(Error::MaxRetriesExceeded { last_error, .. }) = result else {
+        panic!("expected max retries exceeded, got {:?}", result);
+    };
+    match *last_error {
+        Error::Network(e) => {
+            assert!(e.is_connect());
+            assert_eq!(e.to_string(), "connection refused");
+        }
+        other => panic!("expected a network error, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_mock_scripted_timeout_is_retried() {
+    let transport = MockTransport::new();
+    transport.register(
+        Mock::new(Method::GET, "/slow")
+            .respond_with(MockResponse::timeout())
+            .respond_with(MockResponse::new(204))
+            .expect(2),
+    );
+
+    let client = Client::builder()
+        .base_url("https://api.example.com")
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_secs(1),
+            max_retries: 1,
+        })
+        .mock_transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let response = client.get::<()>("/slow").await.unwrap();
+
+    assert_eq!(response.attempts, 2);
+    transport.verify();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_mock_delay_exceeds_client_timeout() {
+    let transport = MockTransport::new();
+    transport.register(
+        Mock::new(Method::GET, "/slow")
+            .respond_with(MockResponse::new(200).delay(Duration::from_secs(60))),
+    );
+
+    let client = Client::builder()
+        .base_url("https://api.example.com")
+        .unwrap()
+        .timeout(Duration::from_secs(5))
+        .mock_transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let start = tokio::time::Instant::now();
+    let result = client.get::<()>("/slow").await;
+
+    match result {
+        Err(Error::MaxRetriesExceeded { last_error, .. }) => {
+            assert!(matches!(*last_error, Error::Timeout { .. }));
+            let request = last_error.request().expect("timeout carries its request");
+            assert_eq!(request.url.as_str(), "https://api.example.com/slow");
+        }
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    assert_eq!(start.elapsed(), Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_mock_matches_query_headers_and_body() {
+    let transport = MockTransport::new();
+    transport.register(
+        Mock::new(Method::POST, "/orders")
+            .query("dry_run", "true")
+            .header("x-api-key", "secret")
+            .json_body(&json!({"item": "book", "quantity": 2}))
+            .respond_with(MockResponse::json(201, &json!({"id": 7})))
+            .expect(1),
+    );
+    transport.register(
+        Mock::new(Method::POST, "/orders")
+            .respond_with(MockResponse::new(400))
+            .expect(1),
+    );
+
+    let client = Client::builder()
+        .base_url("https://api.example.com")
+        .unwrap()
+        .default_header("x-api-key", "secret")
+        .unwrap()
+        .mock_transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let body = json!({"quantity": 2, "item": "book"});
+    let mut metadata = RequestMetadata::new(Method::POST, "/orders");
+    metadata
+        .query_params
+        .insert("dry_run".to_string(), "true".to_string());
+    let created = client
+        .call::<_, serde_json::Value>(metadata, Some(&body))
+        .await
+        .unwrap();
+    assert_eq!(created.data["id"], 7);
+
+    // Without the query parameter, only the second mock matches
+    let result = client.post::<_, serde_json::Value>("/orders", &body).await;
+    assert_eq!(result.unwrap_err().status().unwrap().as_u16(), 400);
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].url.query(), Some("dry_run=true"));
+    assert_eq!(requests[0].headers["content-type"], "application/json");
+    assert_eq!(requests[1].json::<serde_json::Value>().unwrap(), body);
+    transport.verify();
+}
+
+#[tokio::test]
+#[should_panic(expected = "no mock matched GET https://api.example.com/missing")]
+async fn test_mock_verify_reports_unmatched_requests() {
+    let transport = MockTransport::new();
+
+    let result = client(&transport).get::<()>("/missing").await;
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+
+    transport.verify();
+}
+
+#[tokio::test]
+#[should_panic(expected = "GET /users was called 1 times, expected 2")]
+async fn test_mock_verify_reports_unmet_expectations() {
+    let transport = MockTransport::new();
+    transport.register(Mock::new(Method::GET, "/users").expect(2));
+
+    client(&transport).get::<()>("/users").await.unwrap();
+
+    transport.verify();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hedged_request_uses_fastest_response() {
+    // The original request stalls, the hedged request answers immediately
+    let transport = MockTransport::new();
+    transport.register(
+        Mock::new(Method::GET, "/users/1")
+            .respond_with(MockResponse::json(200, &json!({"id": 1})).delay(Duration::from_secs(5)))
+            .respond_with(MockResponse::json(200, &json!({"id": 1})))
+            .expect(2),
+    );
+
+    let client = Client::builder()
+        .base_url("https://api.example.com")
+        .unwrap()
+        .hedge_config(
+            HedgeConfig::builder()
+                .delay(HedgeDelay::Fixed(Duration::from_millis(50)))
+                .build(),
+        )
+        .mock_transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let start = tokio::time::Instant::now();
+    let response = client.get::<serde_json::Value>("/users/1").await.unwrap();
+
+    assert_eq!(response.data["id"], 1);
+    assert_eq!(response.attempts, 1);
+    assert_eq!(response.hedges, 1);
+    assert_eq!(start.elapsed(), Duration::from_millis(50));
+    transport.verify();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hedge_delay_counts_cancelled_requests() {
+    // Every original request stalls and every hedge answers immediately, so
+    // only the cancelled originals show how slow the endpoint is
+    let calls = 30;
+    let mut mock = Mock::new(Method::GET, "/slow");
+    for _ in 0..calls {
+        mock = mock
+            .respond_with(MockResponse::new(204).delay(Duration::from_secs(5)))
+            .respond_with(MockResponse::new(204));
+    }
+    let transport = MockTransport::new();
+    transport.register(mock);
+
+    let client = Client::builder()
+        .base_url("https://api.example.com")
+        .unwrap()
+        .hedge_config(
+            HedgeConfig::builder()
+                .delay(HedgeDelay::Percentile {
+                    percentile: 50.0,
+                    fallback: Duration::from_millis(100),
+                })
+                .build(),
+        )
+        .mock_transport(transport.clone())
+        .build()
+        .unwrap();
+
+    for _ in 0..calls {
+        let start = tokio::time::Instant::now();
+        let response = client.get::<()>("/slow").await.unwrap();
+        assert_eq!(response.hedges, 1);
+        // The hedge delay doesn't drift towards the hedges' own latency
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,174 @@
+//! Integration tests for OpenTelemetry spans and trace context propagation.
+
+#![cfg(feature = "opentelemetry")]
+
+use calleen::telemetry::{B3Format, TracePropagation};
+use calleen::{Client, RetryStrategy};
+use opentelemetry::trace::{SpanKind, TracerProvider as _};
+use opentelemetry::Value;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::layer::SubscriberExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn install_exporter() -> (InMemorySpanExporter, tracing::subscriber::DefaultGuard) {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("calleen-test")));
+    let guard = tracing::subscriber::set_default(subscriber);
+    (exporter, guard)
+}
+
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+    span.attributes
+        .iter()
+        .find(|kv| kv.key.as_str() == key)
+        .map(|kv| &kv.value)
+}
+
+#[tokio::test]
+async fn test_call_and_attempt_spans() {
+    let (exporter, _guard) = install_exporter();
+    let mock_server = MockServer::start().await;
+
+    let attempt_count = Arc::new(AtomicUsize::new(0));
+    let attempt_count_clone = attempt_count.clone();
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(move |_req: &wiremock::Request| {
+            if attempt_count_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                ResponseTemplate::new(500)
+            } else {
+                ResponseTemplate::new(200).set_body_string("{}")
+            }
+        })
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .retry_strategy(RetryStrategy::Linear {
+            delay: Duration::from_millis(10),
+            max_retries: 2,
+        })
+        .build()
+        .unwrap();
+
+    client.get::<serde_json::Value>("/test").await.unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 3);
+
+    let call = spans
+        .iter()
+        .find(|s| {
+            s.name == "GET"
+                && attribute(s, "http.response.status_code") == Some(&Value::I64(200))
+                && s.parent_span_id == opentelemetry::trace::SpanId::INVALID
+        })
+        .expect("call span");
+    assert_eq!(call.span_kind, SpanKind::Client);
+    assert_eq!(
+        attribute(call, "http.request.resend_count"),
+        Some(&Value::I64(1))
+    );
+
+    let attempts: Vec<_> = spans
+        .iter()
+        .filter(|s| s.parent_span_id == call.span_context.span_id())
+        .collect();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(
+        attribute(attempts[0], "http.response.status_code"),
+        Some(&Value::I64(500))
+    );
+    assert_eq!(
+        attribute(attempts[0], "error.type"),
+        Some(&Value::String("500".into()))
+    );
+    assert_eq!(attribute(attempts[0], "http.request.resend_count"), None);
+    assert_eq!(
+        attribute(attempts[1], "http.request.resend_count"),
+        Some(&Value::I64(1))
+    );
+
+    // Each request carries the trace context of its attempt span
+    let requests = mock_server.received_requests().await.unwrap();
+    for (request, attempt) in requests.iter().zip(&attempts) {
+        let traceparent = request
+            .headers
+            .get("traceparent")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(
+            traceparent,
+            format!(
+                "00-{}-{}-01",
+                attempt.span_context.trace_id(),
+                attempt.span_context.span_id()
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_b3_propagation() {
+    let (_exporter, _guard) = install_exporter();
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .trace_propagation(TracePropagation {
+            w3c: false,
+            b3: Some(B3Format::Multi),
+        })
+        .build()
+        .unwrap();
+
+    client.get::<serde_json::Value>("/test").await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let headers = &requests[0].headers;
+    assert!(headers.get("traceparent").is_none());
+    assert_eq!(headers.get("x-b3-traceid").unwrap().len(), 32);
+    assert_eq!(headers.get("x-b3-spanid").unwrap().len(), 16);
+    assert_eq!(headers.get("x-b3-sampled").unwrap(), "1");
+}
+
+#[tokio::test]
+async fn test_no_headers_without_tracing_layer() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::builder()
+        .base_url(mock_server.uri())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    client.get::<serde_json::Value>("/test").await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("traceparent").is_none());
+}
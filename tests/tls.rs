@@ -0,0 +1,251 @@
+//! Integration tests for TLS settings against a local TLS server.
+
+use calleen::tls::{spki_sha256, Certificate, Identity, TlsConfig, TlsVersion};
+use calleen::{Client, Error};
+use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, IsCa, KeyPair};
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use rustls_pki_types::PrivateKeyDer;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+fn certificate_authority(name: &str) -> CertifiedIssuer<'static, KeyPair> {
+    let mut params = CertificateParams::new(Vec::new()).unwrap();
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, name);
+    CertifiedIssuer::self_signed(params, KeyPair::generate().unwrap()).unwrap()
+}
+
+fn leaf(ca: &CertifiedIssuer<'static, KeyPair>, name: &str) -> (rcgen::Certificate, KeyPair) {
+    let key = KeyPair::generate().unwrap();
+    let certificate = CertificateParams::new(vec![name.to_string()])
+        .unwrap()
+        .signed_by(&key, ca)
+        .unwrap();
+    (certificate, key)
+}
+
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// Starts an HTTPS server for `localhost`, returning its port and certificate.
+///
+/// Every response body reports whether the client presented a certificate.
+async fn serve(
+    ca: &CertifiedIssuer<'static, KeyPair>,
+    client_ca: Option<&CertifiedIssuer<'static, KeyPair>>,
+    versions: &[&'static rustls::SupportedProtocolVersion],
+) -> (u16, Vec<u8>) {
+    let (certificate, key) = leaf(ca, "localhost");
+    let builder = rustls::ServerConfig::builder_with_provider(provider())
+        .with_protocol_versions(versions)
+        .unwrap();
+    let builder = match client_ca {
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            roots.add(client_ca.der().clone()).unwrap();
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider())
+                .build()
+                .unwrap();
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let der = certificate.der().to_vec();
+    let config = builder
+        .with_single_cert(
+            vec![certificate.der().clone()],
+            PrivateKeyDer::Pkcs8(key.serialize_der().into()),
+        )
+        .unwrap();
+
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                // Handshake failures are what some tests expect
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let client_cert = stream.get_ref().1.peer_certificates().is_some();
+
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let body = serde_json::json!({ "client_cert": client_cert }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    (port, der)
+}
+
+fn client(port: u16, config: TlsConfig) -> Client {
+    Client::builder()
+        .base_url(format!("https://localhost:{}", port))
+        .unwrap()
+        .tls_config(config)
+        .build()
+        .unwrap()
+}
+
+/// Asserts that a request failed with a network error mentioning `reason`.
+fn assert_network_error(error: Error, reason: &str) {
+    let Error::MaxRetriesExceeded { last_error, .. } = error else {
+        panic!("expected max retries exceeded, got {:?}", error);
+    };
+    match *last_error {
+        Error::Network(e) => assert!(
+            e.to_string().contains(reason),
+            "expected {:?} in {:?}",
+            reason,
+            e.to_string()
+        ),
+        other => panic!("expected a network error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_private_root_certificate() {
+    let ca = certificate_authority("Test CA");
+    let (port, _) = serve(&ca, None, rustls::DEFAULT_VERSIONS).await;
+
+    let trusted = client(
+        port,
+        TlsConfig::builder()
+            .root_certificate(Certificate::Pem(ca.pem().into_bytes()))
+            .built_in_roots(false)
+            .build(),
+    );
+    let response = trusted.get::<serde_json::Value>("/").await.unwrap();
+    assert_eq!(response.data["client_cert"], false);
+
+    // The built-in roots don't include the private CA
+    let untrusted = client(port, TlsConfig::default());
+    let error = untrusted.get::<serde_json::Value>("/").await.unwrap_err();
+    assert_network_error(error, "UnknownIssuer");
+}
+
+#[tokio::test]
+async fn test_client_identity() {
+    let ca = certificate_authority("Test CA");
+    let client_ca = certificate_authority("Client CA");
+    let (port, _) = serve(&ca, Some(&client_ca), rustls::DEFAULT_VERSIONS).await;
+    let (certificate, key) = leaf(&client_ca, "client.local");
+
+    let with_identity = client(
+        port,
+        TlsConfig::builder()
+            .root_certificate(Certificate::Der(ca.der().to_vec()))
+            .identity(Identity::from_pem(certificate.pem(), key.serialize_pem()))
+            .build(),
+    );
+    let response = with_identity.get::<serde_json::Value>("/").await.unwrap();
+    assert_eq!(response.data["client_cert"], true);
+
+    let without_identity = client(
+        port,
+        TlsConfig::builder()
+            .root_certificate(Certificate::Der(ca.der().to_vec()))
+            .build(),
+    );
+    assert!(without_identity
+        .get::<serde_json::Value>("/")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_spki_pinning() {
+    let ca = certificate_authority("Test CA");
+    let (port, server_der) = serve(&ca, None, rustls::DEFAULT_VERSIONS).await;
+    let roots = || {
+        TlsConfig::builder()
+            .root_certificate(Certificate::Pem(ca.pem().into_bytes()))
+            .built_in_roots(false)
+    };
+
+    let pinned = client(
+        port,
+        roots()
+            .pin_spki_sha256(spki_sha256(&server_der).unwrap())
+            .build(),
+    );
+    assert!(pinned.get::<serde_json::Value>("/").await.is_ok());
+
+    // The CA's certificate isn't part of the chain the server presents
+    let mispinned = client(
+        port,
+        roots()
+            .pin_spki_sha256(spki_sha256(ca.der()).unwrap())
+            .build(),
+    );
+    let error = mispinned.get::<serde_json::Value>("/").await.unwrap_err();
+    assert_network_error(error, "pinned key");
+}
+
+#[tokio::test]
+async fn test_min_tls_version() {
+    let ca = certificate_authority("Test CA");
+    let (port, _) = serve(&ca, None, &[&rustls::version::TLS12]).await;
+    let config = |version| {
+        TlsConfig::builder()
+            .root_certificate(Certificate::Pem(ca.pem().into_bytes()))
+            .min_version(version)
+            .build()
+    };
+
+    let tls12 = client(port, config(TlsVersion::Tls12));
+    assert!(tls12.get::<serde_json::Value>("/").await.is_ok());
+
+    let tls13 = client(port, config(TlsVersion::Tls13));
+    assert!(tls13.get::<serde_json::Value>("/").await.is_err());
+}
+
+#[test]
+fn test_invalid_tls_config() {
+    let build = |config| {
+        Client::builder()
+            .base_url("https://localhost")
+            .unwrap()
+            .tls_config(config)
+            .build()
+    };
+
+    let result = build(
+        TlsConfig::builder()
+            .root_certificate(Certificate::Pem(b"not a certificate".to_vec()))
+            .build(),
+    );
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+
+    let result = build(
+        TlsConfig::builder()
+            .identity(Identity::from_pem("", "not a key"))
+            .build(),
+    );
+    assert!(matches!(result, Err(Error::ConfigurationError(_))));
+}